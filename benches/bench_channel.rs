@@ -0,0 +1,169 @@
+#![feature(test)]
+#![allow(deprecated)]
+
+extern crate mio;
+extern crate test;
+
+use mio::*;
+use mio::channel::{self, ReceiverCtl, SenderCtl};
+use test::Bencher;
+use std::sync::mpsc;
+use std::thread;
+
+const NUM: usize = 10_000;
+const THREADS: usize = 4;
+
+// The implementation `channel::channel` used before the lock-free queue:
+// `std::sync::mpsc` plus a separate pending counter.
+struct MpscSender<T> {
+    tx: mpsc::Sender<T>,
+    ctl: SenderCtl,
+}
+
+struct MpscReceiver<T> {
+    rx: mpsc::Receiver<T>,
+    ctl: ReceiverCtl,
+}
+
+fn mpsc_channel<T>() -> (MpscSender<T>, MpscReceiver<T>) {
+    let (tx_ctl, rx_ctl) = channel::ctl_pair();
+    let (tx, rx) = mpsc::channel();
+
+    (MpscSender { tx: tx, ctl: tx_ctl }, MpscReceiver { rx: rx, ctl: rx_ctl })
+}
+
+impl<T> MpscSender<T> {
+    fn send(&self, t: T) {
+        self.tx.send(t).unwrap();
+        self.ctl.inc().unwrap();
+    }
+}
+
+impl<T> Clone for MpscSender<T> {
+    fn clone(&self) -> MpscSender<T> {
+        MpscSender { tx: self.tx.clone(), ctl: self.ctl.clone() }
+    }
+}
+
+impl<T> MpscReceiver<T> {
+    fn try_recv(&self) -> Result<T, mpsc::TryRecvError> {
+        self.rx.try_recv().map(|t| {
+            self.ctl.dec().unwrap();
+            t
+        })
+    }
+}
+
+#[bench]
+fn bench_channel_send_recv(bench: &mut Bencher) {
+    let poll = Poll::new().unwrap();
+    let (tx, rx) = channel::channel();
+
+    poll.register(&rx, Token(0), Ready::readable(), PollOpt::edge()).unwrap();
+
+    bench.iter(|| {
+        for i in 0..NUM {
+            tx.send(i).unwrap();
+        }
+
+        while let Ok(_) = rx.try_recv() {}
+    })
+}
+
+#[bench]
+fn bench_channel_send_recv_many(bench: &mut Bencher) {
+    let poll = Poll::new().unwrap();
+    let (tx, rx) = channel::channel();
+    let mut values = Vec::with_capacity(NUM);
+
+    poll.register(&rx, Token(0), Ready::readable(), PollOpt::edge()).unwrap();
+
+    bench.iter(|| {
+        for i in 0..NUM {
+            tx.send(i).unwrap();
+        }
+
+        values.clear();
+        while let Ok(_) = rx.try_recv_many(&mut values, 256) {}
+    })
+}
+
+#[bench]
+fn bench_mpsc_send_recv(bench: &mut Bencher) {
+    let poll = Poll::new().unwrap();
+    let (tx, rx) = mpsc_channel();
+
+    poll.register(&rx.ctl, Token(0), Ready::readable(), PollOpt::edge()).unwrap();
+
+    bench.iter(|| {
+        for i in 0..NUM {
+            tx.send(i);
+        }
+
+        while let Ok(_) = rx.try_recv() {}
+    })
+}
+
+#[bench]
+fn bench_channel_multi_producer(bench: &mut Bencher) {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(1024);
+    let (tx, rx) = channel::channel();
+    let mut values = Vec::with_capacity(NUM);
+
+    poll.register(&rx, Token(0), Ready::readable(), PollOpt::edge()).unwrap();
+
+    bench.iter(|| {
+        for t in 0..THREADS {
+            let tx = tx.clone();
+
+            thread::spawn(move || {
+                for i in (t..NUM).filter(|i| i % THREADS == t) {
+                    tx.send(i).unwrap();
+                }
+            });
+        }
+
+        let mut n = 0;
+        values.clear();
+
+        while n < NUM {
+            poll.poll(&mut events, None).unwrap();
+
+            while let Ok(cnt) = rx.try_recv_many(&mut values, 256) {
+                n += cnt;
+            }
+        }
+    })
+}
+
+#[bench]
+fn bench_mpsc_multi_producer(bench: &mut Bencher) {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(1024);
+    let (tx, rx) = mpsc_channel();
+
+    poll.register(&rx.ctl, Token(0), Ready::readable(), PollOpt::edge()).unwrap();
+
+    bench.iter(|| {
+        for t in 0..THREADS {
+            let tx = tx.clone();
+
+            thread::spawn(move || {
+                for i in (t..NUM).filter(|i| i % THREADS == t) {
+                    tx.send(i);
+                }
+            });
+        }
+
+        let mut n = 0;
+
+        while n < NUM {
+            poll.poll(&mut events, None).unwrap();
+
+            while let Ok(_) = rx.try_recv() {
+                n += 1;
+            }
+        }
+    })
+}
@@ -0,0 +1,290 @@
+//! Broadcast channel where every subscriber can be registered with `Poll`.
+//!
+//! Sent values are kept in a ring buffer shared by all subscribers. Each
+//! subscriber tracks its own position in the buffer. A subscriber that falls
+//! more than `capacity` values behind loses the oldest values and is told by
+//! how many it lagged.
+
+use {io, Ready, Poll, PollOpt, Registration, SetReadiness, Token};
+use event::Evented;
+use lazycell::LazyCell;
+use slab::Slab;
+use std::collections::VecDeque;
+use std::error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use super::SendError;
+
+/// Creates a new broadcast channel holding up to `capacity` values.
+///
+/// Every value sent is received by each `BroadcastReceiver` subscribed at the
+/// time it was sent. More receivers can be created with
+/// `BroadcastSender::subscribe`.
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub fn broadcast<T: Clone>(capacity: usize) -> (BroadcastSender<T>, BroadcastReceiver<T>) {
+    assert!(capacity > 0, "broadcast channel capacity must be greater than zero");
+
+    let shared = Arc::new(Mutex::new(Shared {
+        buffer: VecDeque::with_capacity(capacity),
+        capacity: capacity,
+        head: 0,
+        senders: 1,
+        subscribers: Slab::new(),
+    }));
+
+    let tx = BroadcastSender {
+        shared: shared,
+    };
+
+    let rx = tx.subscribe();
+
+    (tx, rx)
+}
+
+pub struct BroadcastSender<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+}
+
+pub struct BroadcastReceiver<T> {
+    shared: Arc<Mutex<Shared<T>>>,
+    // Key into `Shared::subscribers`
+    key: usize,
+    registration: LazyCell<Registration>,
+}
+
+pub enum BroadcastTryRecvError {
+    /// No new values have been sent.
+    Empty,
+    /// The receiver fell behind and missed this many values. The next call
+    /// to `try_recv` returns the oldest value still buffered.
+    Lagged(u64),
+    /// All senders are gone and every value has been received.
+    Disconnected,
+}
+
+struct Shared<T> {
+    // Values that may not have been received by every subscriber
+    buffer: VecDeque<T>,
+    // Max number of values held in `buffer`
+    capacity: usize,
+    // Position of the first value in `buffer`
+    head: u64,
+    // The number of sender handles
+    senders: usize,
+    subscribers: Slab<Subscriber>,
+}
+
+struct Subscriber {
+    // Position of the next value to receive
+    pos: u64,
+    // Set on registration with Poll
+    set_readiness: Option<SetReadiness>,
+}
+
+impl<T: Clone> BroadcastSender<T> {
+    /// Sends a value to all subscribers.
+    ///
+    /// Fails with `Disconnected` if there are no subscribers.
+    pub fn send(&self, t: T) -> Result<(), SendError<T>> {
+        let mut shared = self.shared.lock().unwrap();
+
+        if shared.subscribers.is_empty() {
+            return Err(SendError::Disconnected(t));
+        }
+
+        let tail = shared.tail();
+
+        if shared.buffer.len() == shared.capacity {
+            shared.buffer.pop_front();
+            shared.head += 1;
+        }
+
+        shared.buffer.push_back(t);
+
+        // Subscribers that had not yet caught up are already readable
+        for (_, subscriber) in shared.subscribers.iter() {
+            if subscriber.pos == tail {
+                if let Some(ref set_readiness) = subscriber.set_readiness {
+                    set_readiness.set_readiness(Ready::readable())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates a new receiver that receives all values sent from now on.
+    pub fn subscribe(&self) -> BroadcastReceiver<T> {
+        let mut shared = self.shared.lock().unwrap();
+
+        let pos = shared.tail();
+        let key = shared.subscribers.insert(Subscriber {
+            pos: pos,
+            set_readiness: None,
+        });
+
+        BroadcastReceiver {
+            shared: self.shared.clone(),
+            key: key,
+            registration: LazyCell::new(),
+        }
+    }
+}
+
+impl<T> Clone for BroadcastSender<T> {
+    fn clone(&self) -> BroadcastSender<T> {
+        self.shared.lock().unwrap().senders += 1;
+        BroadcastSender { shared: self.shared.clone() }
+    }
+}
+
+impl<T> Drop for BroadcastSender<T> {
+    fn drop(&mut self) {
+        let mut shared = self.shared.lock().unwrap();
+
+        shared.senders -= 1;
+
+        if shared.senders == 0 {
+            for (_, subscriber) in shared.subscribers.iter() {
+                if let Some(ref set_readiness) = subscriber.set_readiness {
+                    let _ = set_readiness.set_readiness(Ready::readable() | Ready::hup());
+                }
+            }
+        }
+    }
+}
+
+impl<T: Clone> BroadcastReceiver<T> {
+    pub fn try_recv(&self) -> Result<T, BroadcastTryRecvError> {
+        let mut shared = self.shared.lock().unwrap();
+
+        let head = shared.head;
+        let tail = shared.tail();
+        let pos = shared.subscribers[self.key].pos;
+
+        if pos < head {
+            // Skip to the oldest value still buffered
+            shared.subscribers[self.key].pos = head;
+            return Err(BroadcastTryRecvError::Lagged(head - pos));
+        }
+
+        if pos == tail {
+            return Err(if shared.senders == 0 {
+                BroadcastTryRecvError::Disconnected
+            } else {
+                BroadcastTryRecvError::Empty
+            });
+        }
+
+        let t = shared.buffer[(pos - head) as usize].clone();
+        shared.subscribers[self.key].pos = pos + 1;
+
+        if pos + 1 == tail {
+            let _ = shared.update_readiness(self.key);
+        }
+
+        Ok(t)
+    }
+}
+
+impl<T> Drop for BroadcastReceiver<T> {
+    fn drop(&mut self) {
+        self.shared.lock().unwrap().subscribers.remove(self.key);
+    }
+}
+
+impl<T> Evented for BroadcastReceiver<T> {
+    fn register(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+        if self.registration.borrow().is_some() {
+            return Err(io::Error::new(io::ErrorKind::Other, "receiver already registered"));
+        }
+
+        // Like the system selector, always report hup
+        let (registration, set_readiness) = Registration::new(poll, token, interest | Ready::hup(), opts);
+
+        self.registration.fill(registration).ok().expect("unexpected state encountered");
+
+        let mut shared = self.shared.lock().unwrap();
+        shared.subscribers[self.key].set_readiness = Some(set_readiness);
+        shared.update_readiness(self.key)
+    }
+
+    fn reregister(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+        match self.registration.borrow() {
+            Some(registration) => registration.update(poll, token, interest | Ready::hup(), opts),
+            None => Err(io::Error::new(io::ErrorKind::Other, "receiver not registered")),
+        }
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        match self.registration.borrow() {
+            Some(registration) => registration.deregister(poll),
+            None => Err(io::Error::new(io::ErrorKind::Other, "receiver not registered")),
+        }
+    }
+}
+
+impl<T> Shared<T> {
+    // Position of the next value to be sent
+    fn tail(&self) -> u64 {
+        self.head + self.buffer.len() as u64
+    }
+
+    fn update_readiness(&self, key: usize) -> io::Result<()> {
+        let subscriber = &self.subscribers[key];
+
+        let set_readiness = match subscriber.set_readiness {
+            Some(ref set_readiness) => set_readiness,
+            None => return Ok(()),
+        };
+
+        if self.senders == 0 {
+            set_readiness.set_readiness(Ready::readable() | Ready::hup())
+        } else if subscriber.pos < self.tail() {
+            set_readiness.set_readiness(Ready::readable())
+        } else {
+            set_readiness.set_readiness(Ready::empty())
+        }
+    }
+}
+
+/*
+ *
+ * ===== Implement Error, Debug and Display for Errors =====
+ *
+ */
+
+impl error::Error for BroadcastTryRecvError {
+    fn description(&self) -> &str {
+        match *self {
+            BroadcastTryRecvError::Empty => "Empty",
+            BroadcastTryRecvError::Lagged(..) => "Lagged",
+            BroadcastTryRecvError::Disconnected => "Disconnected",
+        }
+    }
+}
+
+impl fmt::Debug for BroadcastTryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        format_try_recv_error(self, f)
+    }
+}
+
+impl fmt::Display for BroadcastTryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        format_try_recv_error(self, f)
+    }
+}
+
+#[inline]
+fn format_try_recv_error(e: &BroadcastTryRecvError, f: &mut fmt::Formatter) -> fmt::Result {
+    match *e {
+        BroadcastTryRecvError::Empty => write!(f, "Empty"),
+        BroadcastTryRecvError::Lagged(n) => write!(f, "lagged by {}", n),
+        BroadcastTryRecvError::Disconnected => write!(f, "Disconnected"),
+    }
+}
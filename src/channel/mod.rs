@@ -0,0 +1,679 @@
+//! Thread safe communication channel implementing `Evented`
+
+#![allow(unused_imports, deprecated, missing_debug_implementations)]
+
+use {io, Ready, Poll, PollOpt, Registration, SetReadiness, Token};
+use event::Evented;
+use lazycell::{LazyCell, AtomicLazyCell};
+use std::any::Any;
+use std::{fmt, usize};
+use std::error;
+use std::sync::{mpsc, Arc};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+mod broadcast;
+mod oneshot;
+mod queue;
+mod semaphore;
+
+pub use self::broadcast::{
+    broadcast,
+    BroadcastSender,
+    BroadcastReceiver,
+    BroadcastTryRecvError,
+};
+pub use self::oneshot::{
+    oneshot,
+    OneshotSender,
+    OneshotReceiver,
+};
+pub use self::semaphore::{
+    Semaphore,
+    SemaphoreHandle,
+};
+
+/// Creates a new asynchronous channel, where the `Receiver` can be registered
+/// with `Poll`.
+///
+/// Once all senders are dropped, the `Receiver` is readable with `hup`
+/// readiness.
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let (tx, rx) = queue::queue();
+
+    let tx = Sender {
+        tx: tx,
+    };
+
+    let rx = Receiver {
+        inner: ReceiverInner::Queue(rx),
+    };
+
+    (tx, rx)
+}
+
+/// Creates a new synchronous, bounded channel where both the `SyncSender` and
+/// the `Receiver` can be registered with `Poll`.
+///
+/// The `SyncSender` is writable while the channel has spare capacity. Once the
+/// `Receiver` is dropped, the `SyncSender` is writable with `hup` readiness.
+///
+/// Only one `SyncSender` of a channel can be registered, and only once.
+/// Registering any of its clones afterwards fails, even if the first one has
+/// since been deregistered or dropped.
+pub fn sync_channel<T>(bound: usize) -> (SyncSender<T>, Receiver<T>) {
+    let (tx_ctl, rx_ctl) = bounded_ctl_pair(bound);
+    let (tx, rx) = mpsc::sync_channel(bound);
+
+    let tx = SyncSender {
+        tx: tx,
+        ctl: tx_ctl,
+    };
+
+    let rx = Receiver {
+        inner: ReceiverInner::Sync(rx, rx_ctl),
+    };
+
+    (tx, rx)
+}
+
+pub fn ctl_pair() -> (SenderCtl, ReceiverCtl) {
+    bounded_ctl_pair(usize::MAX)
+}
+
+/// Like `ctl_pair`, but the `SenderCtl` is only writable while fewer than
+/// `bound` messages are pending.
+pub fn bounded_ctl_pair(bound: usize) -> (SenderCtl, ReceiverCtl) {
+    let inner = Arc::new(Inner {
+        pending: AtomicUsize::new(0),
+        senders: AtomicUsize::new(1),
+        bound: bound,
+        receiver_dropped: AtomicBool::new(false),
+        set_readiness: AtomicLazyCell::new(),
+        tx_set_readiness: AtomicLazyCell::new(),
+    });
+
+    let tx = SenderCtl {
+        registration: LazyCell::new(),
+        inner: inner.clone(),
+    };
+
+    let rx = ReceiverCtl {
+        registration: LazyCell::new(),
+        inner: inner,
+    };
+
+    (tx, rx)
+}
+
+/// Tracks messages sent on a channel in order to update readiness.
+pub struct SenderCtl {
+    registration: LazyCell<Registration>,
+    inner: Arc<Inner>,
+}
+
+/// Tracks messages received on a channel in order to track readiness.
+pub struct ReceiverCtl {
+    registration: LazyCell<Registration>,
+    inner: Arc<Inner>,
+}
+
+pub struct Sender<T> {
+    tx: queue::Sender<T>,
+}
+
+/// The sending half of a channel created by `sync_channel`.
+///
+/// See [`sync_channel`] for how a `SyncSender` can be registered with `Poll`.
+///
+/// [`sync_channel`]: fn.sync_channel.html
+pub struct SyncSender<T> {
+    tx: mpsc::SyncSender<T>,
+    ctl: SenderCtl,
+}
+
+pub struct Receiver<T> {
+    inner: ReceiverInner<T>,
+}
+
+enum ReceiverInner<T> {
+    // Created by `channel`
+    Queue(queue::Receiver<T>),
+    // Created by `sync_channel`
+    Sync(mpsc::Receiver<T>, ReceiverCtl),
+}
+
+pub enum SendError<T> {
+    Io(io::Error),
+    Disconnected(T),
+}
+
+pub enum TrySendError<T> {
+    Io(io::Error),
+    Full(T),
+    Disconnected(T),
+}
+
+struct Inner {
+    // The number of outstanding messages for the receiver to read
+    pending: AtomicUsize,
+    // The number of sender handles
+    senders: AtomicUsize,
+    // The number of pending messages at which senders are no longer writable
+    bound: usize,
+    // Set when the receiver handle is dropped
+    receiver_dropped: AtomicBool,
+    // The set readiness handle
+    set_readiness: AtomicLazyCell<SetReadiness>,
+    // The set readiness handle of the registered sender
+    tx_set_readiness: AtomicLazyCell<SetReadiness>,
+}
+
+impl<T> Sender<T> {
+    pub fn send(&self, t: T) -> Result<(), SendError<T>> {
+        self.tx.send(t)
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        Sender {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<T> SyncSender<T> {
+    pub fn send(&self, t: T) -> Result<(), SendError<T>> {
+        self.tx.send(t)
+            .map_err(From::from)
+            .and_then(|_| {
+                self.ctl.inc()?;
+                Ok(())
+            })
+    }
+
+    pub fn try_send(&self, t: T) -> Result<(), TrySendError<T>> {
+        self.tx.try_send(t)
+            .map_err(From::from)
+            .and_then(|_| {
+                self.ctl.inc()?;
+                Ok(())
+            })
+    }
+}
+
+impl<T> Clone for SyncSender<T> {
+    fn clone(&self) -> SyncSender<T> {
+        SyncSender {
+            tx: self.tx.clone(),
+            ctl: self.ctl.clone(),
+        }
+    }
+}
+
+impl<T> Evented for SyncSender<T> {
+    fn register(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+        self.ctl.register(poll, token, interest, opts)
+    }
+
+    fn reregister(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+        self.ctl.reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        self.ctl.deregister(poll)
+    }
+}
+
+impl<T> Receiver<T> {
+    pub fn try_recv(&self) -> Result<T, mpsc::TryRecvError> {
+        match self.inner {
+            ReceiverInner::Queue(ref rx) => rx.try_recv(),
+            ReceiverInner::Sync(ref rx, ref ctl) => {
+                rx.try_recv().and_then(|res| {
+                    let _ = ctl.dec();
+                    Ok(res)
+                })
+            }
+        }
+    }
+
+    /// Receives up to `max` values, appending them to `dst`.
+    ///
+    /// Returns the number of values received. If no values could be received,
+    /// returns the same error as `try_recv` would.
+    pub fn try_recv_many(&self, dst: &mut Vec<T>, max: usize) -> Result<usize, mpsc::TryRecvError> {
+        match self.inner {
+            ReceiverInner::Queue(ref rx) => rx.try_recv_many(dst, max),
+            ReceiverInner::Sync(..) => {
+                let mut n = 0;
+
+                while n < max {
+                    match self.try_recv() {
+                        Ok(t) => {
+                            dst.push(t);
+                            n += 1;
+                        }
+                        Err(e) => {
+                            if n == 0 {
+                                return Err(e);
+                            }
+
+                            break;
+                        }
+                    }
+                }
+
+                Ok(n)
+            }
+        }
+    }
+}
+
+impl<T> Evented for Receiver<T> {
+    fn register(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+        match self.inner {
+            ReceiverInner::Queue(ref rx) => rx.register(poll, token, interest, opts),
+            ReceiverInner::Sync(_, ref ctl) => ctl.register(poll, token, interest, opts),
+        }
+    }
+
+    fn reregister(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+        match self.inner {
+            ReceiverInner::Queue(ref rx) => rx.reregister(poll, token, interest, opts),
+            ReceiverInner::Sync(_, ref ctl) => ctl.reregister(poll, token, interest, opts),
+        }
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        match self.inner {
+            ReceiverInner::Queue(ref rx) => rx.deregister(poll),
+            ReceiverInner::Sync(_, ref ctl) => ctl.deregister(poll),
+        }
+    }
+}
+
+/*
+ *
+ * ===== SenderCtl / ReceiverCtl =====
+ *
+ */
+
+impl SenderCtl {
+    /// Call to track that a message has been sent
+    pub fn inc(&self) -> io::Result<()> {
+        self.add(1)
+    }
+
+    /// Call to track that `n` messages have been sent
+    ///
+    /// Fails, tracking nothing, if the count would overflow.
+    pub fn add(&self, n: usize) -> io::Result<()> {
+        if n == 0 {
+            return Ok(());
+        }
+
+        let mut cnt = self.inner.pending.load(Ordering::Acquire);
+
+        loop {
+            let next = match cnt.checked_add(n) {
+                Some(next) => next,
+                None => return Err(io::Error::new(io::ErrorKind::Other, "pending count overflow")),
+            };
+
+            let actual = self.inner.pending.compare_and_swap(cnt, next, Ordering::AcqRel);
+
+            if actual == cnt {
+                break;
+            }
+
+            cnt = actual;
+        }
+
+        if 0 == cnt {
+            // Toggle readiness to readable
+            if let Some(set_readiness) = self.inner.set_readiness.borrow() {
+                set_readiness.set_readiness(Ready::readable())?;
+            }
+        }
+
+        if cnt < self.inner.bound && cnt + n >= self.inner.bound {
+            // The channel is now full
+            self.inner.update_tx_readiness()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Clone for SenderCtl {
+    fn clone(&self) -> SenderCtl {
+        self.inner.senders.fetch_add(1, Ordering::Relaxed);
+        SenderCtl {
+            registration: LazyCell::new(),
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl Evented for SenderCtl {
+    fn register(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+        if self.registration.borrow().is_some() || self.inner.tx_set_readiness.borrow().is_some() {
+            return Err(io::Error::new(io::ErrorKind::Other, "sender already registered"));
+        }
+
+        // Like the system selector, always report hup
+        let (registration, set_readiness) = Registration::new(poll, token, interest | Ready::hup(), opts);
+
+        // Another clone may have won the race since the check above, in
+        // which case this registration is dropped without being kept
+        if self.inner.tx_set_readiness.fill(set_readiness).is_err() {
+            return Err(io::Error::new(io::ErrorKind::Other, "sender already registered"));
+        }
+
+        self.registration.fill(registration).ok().expect("unexpected state encountered");
+
+        self.inner.update_tx_readiness()
+    }
+
+    fn reregister(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+        match self.registration.borrow() {
+            Some(registration) => registration.update(poll, token, interest | Ready::hup(), opts),
+            None => Err(io::Error::new(io::ErrorKind::Other, "sender not registered")),
+        }
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        match self.registration.borrow() {
+            Some(registration) => registration.deregister(poll),
+            None => Err(io::Error::new(io::ErrorKind::Other, "sender not registered")),
+        }
+    }
+}
+
+impl Drop for SenderCtl {
+    fn drop(&mut self) {
+        if self.inner.senders.fetch_sub(1, Ordering::Release) == 1 {
+            let _ = self.inner.set_rx_closed();
+        }
+    }
+}
+
+impl ReceiverCtl {
+    pub fn dec(&self) -> io::Result<()> {
+        let first = self.inner.pending.load(Ordering::Acquire);
+
+        if first == 1 {
+            // Unset readiness
+            if let Some(set_readiness) = self.inner.set_readiness.borrow() {
+                set_readiness.set_readiness(Ready::empty())?;
+            }
+        }
+
+        // Decrement
+        let second = self.inner.pending.fetch_sub(1, Ordering::AcqRel);
+
+        if first == 1 && second > 1 {
+            // There are still pending messages. Since readiness was
+            // previously unset, it must be reset here
+            if let Some(set_readiness) = self.inner.set_readiness.borrow() {
+                set_readiness.set_readiness(Ready::readable())?;
+            }
+        }
+
+        if second == self.inner.bound {
+            // Capacity was freed up
+            self.inner.update_tx_readiness()?;
+        }
+
+        if self.inner.senders.load(Ordering::Acquire) == 0 {
+            // All senders are gone, so readiness must not stay unset
+            self.inner.set_rx_closed()?;
+        }
+
+        Ok(())
+    }
+
+    /// Takes `n` pending messages at once if at least `n` are pending.
+    /// Returns `false` and takes nothing otherwise.
+    pub fn try_sub(&self, n: usize) -> io::Result<bool> {
+        let mut curr = self.inner.pending.load(Ordering::Acquire);
+
+        loop {
+            if curr < n {
+                return Ok(false);
+            }
+
+            let actual = self.inner.pending.compare_and_swap(curr, curr - n, Ordering::AcqRel);
+
+            if actual == curr {
+                break;
+            }
+
+            curr = actual;
+        }
+
+        if n > 0 && curr == n {
+            // Unset readiness
+            if let Some(set_readiness) = self.inner.set_readiness.borrow() {
+                set_readiness.set_readiness(Ready::empty())?;
+
+                // `inc` may have set readiness before it was unset above
+                if self.inner.pending.load(Ordering::Acquire) > 0 {
+                    set_readiness.set_readiness(Ready::readable())?;
+                }
+            }
+        }
+
+        if curr >= self.inner.bound && curr - n < self.inner.bound {
+            // Capacity was freed up
+            self.inner.update_tx_readiness()?;
+        }
+
+        if self.inner.senders.load(Ordering::Acquire) == 0 {
+            // All senders are gone, so readiness must not stay unset
+            self.inner.set_rx_closed()?;
+        }
+
+        Ok(true)
+    }
+
+    /// Returns the number of pending messages.
+    pub fn pending(&self) -> usize {
+        self.inner.pending.load(Ordering::Acquire)
+    }
+}
+
+impl Drop for ReceiverCtl {
+    fn drop(&mut self) {
+        self.inner.receiver_dropped.store(true, Ordering::Release);
+        let _ = self.inner.update_tx_readiness();
+    }
+}
+
+impl Inner {
+    // Sets the receiver's readiness to readable and hup once all senders are
+    // gone.
+    fn set_rx_closed(&self) -> io::Result<()> {
+        if let Some(set_readiness) = self.set_readiness.borrow() {
+            set_readiness.set_readiness(Ready::readable() | Ready::hup())?;
+        }
+
+        Ok(())
+    }
+
+    // Sets the registered sender's readiness to writable if the channel has
+    // spare capacity, and unsets it otherwise. Once the receiver is gone, the
+    // sender is writable with hup.
+    fn update_tx_readiness(&self) -> io::Result<()> {
+        let set_readiness = match self.tx_set_readiness.borrow() {
+            Some(set_readiness) => set_readiness,
+            None => return Ok(()),
+        };
+
+        loop {
+            let pending = self.pending.load(Ordering::Acquire);
+            let dropped = self.receiver_dropped.load(Ordering::Acquire);
+
+            if dropped {
+                set_readiness.set_readiness(Ready::writable() | Ready::hup())?;
+            } else if pending < self.bound {
+                set_readiness.set_readiness(Ready::writable())?;
+            } else {
+                set_readiness.set_readiness(Ready::empty())?;
+            }
+
+            // If the state changed concurrently, the readiness that was just
+            // set may be stale.
+            if pending == self.pending.load(Ordering::Acquire) &&
+                dropped == self.receiver_dropped.load(Ordering::Acquire)
+            {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl Evented for ReceiverCtl {
+    fn register(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+        if self.registration.borrow().is_some() {
+            return Err(io::Error::new(io::ErrorKind::Other, "receiver already registered"));
+        }
+
+        // Like the system selector, always report hup
+        let (registration, set_readiness) = Registration::new(poll, token, interest | Ready::hup(), opts);
+
+
+        if self.inner.senders.load(Ordering::Acquire) == 0 {
+            // TODO: Don't drop readiness
+            let _ = set_readiness.set_readiness(Ready::readable() | Ready::hup());
+        } else if self.inner.pending.load(Ordering::Relaxed) > 0 {
+            // TODO: Don't drop readiness
+            let _ = set_readiness.set_readiness(Ready::readable());
+        }
+
+        self.registration.fill(registration).ok().expect("unexpected state encountered");
+        self.inner.set_readiness.fill(set_readiness).ok().expect("unexpected state encountered");
+
+        Ok(())
+    }
+
+    fn reregister(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+        match self.registration.borrow() {
+            Some(registration) => registration.update(poll, token, interest | Ready::hup(), opts),
+            None => Err(io::Error::new(io::ErrorKind::Other, "receiver not registered")),
+        }
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        match self.registration.borrow() {
+            Some(registration) => registration.deregister(poll),
+            None => Err(io::Error::new(io::ErrorKind::Other, "receiver not registered")),
+        }
+    }
+}
+
+/*
+ *
+ * ===== Error conversions =====
+ *
+ */
+
+impl<T> From<mpsc::SendError<T>> for SendError<T> {
+    fn from(src: mpsc::SendError<T>) -> SendError<T> {
+        SendError::Disconnected(src.0)
+    }
+}
+
+impl<T> From<io::Error> for SendError<T> {
+    fn from(src: io::Error) -> SendError<T> {
+        SendError::Io(src)
+    }
+}
+
+impl<T> From<mpsc::TrySendError<T>> for TrySendError<T> {
+    fn from(src: mpsc::TrySendError<T>) -> TrySendError<T> {
+        match src {
+            mpsc::TrySendError::Full(v) => TrySendError::Full(v),
+            mpsc::TrySendError::Disconnected(v) => TrySendError::Disconnected(v),
+        }
+    }
+}
+
+impl<T> From<mpsc::SendError<T>> for TrySendError<T> {
+    fn from(src: mpsc::SendError<T>) -> TrySendError<T> {
+        TrySendError::Disconnected(src.0)
+    }
+}
+
+impl<T> From<io::Error> for TrySendError<T> {
+    fn from(src: io::Error) -> TrySendError<T> {
+        TrySendError::Io(src)
+    }
+}
+
+/*
+ *
+ * ===== Implement Error, Debug and Display for Errors =====
+ *
+ */
+
+impl<T: Any> error::Error for SendError<T> {
+    fn description(&self) -> &str {
+        match self {
+            &SendError::Io(ref io_err) => io_err.description(),
+            &SendError::Disconnected(..) => "Disconnected",
+        }
+    }
+}
+
+impl<T: Any> error::Error for TrySendError<T> {
+    fn description(&self) -> &str {
+        match self {
+            &TrySendError::Io(ref io_err) => io_err.description(),
+            &TrySendError::Full(..) => "Full",
+            &TrySendError::Disconnected(..) => "Disconnected",
+        }
+    }
+}
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        format_send_error(self, f)
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        format_send_error(self, f)
+    }
+}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        format_try_send_error(self, f)
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        format_try_send_error(self, f)
+    }
+}
+
+#[inline]
+fn format_send_error<T>(e: &SendError<T>, f: &mut fmt::Formatter) -> fmt::Result {
+    match e {
+        &SendError::Io(ref io_err) => write!(f, "{}", io_err),
+        &SendError::Disconnected(..) => write!(f, "Disconnected"),
+    }
+}
+
+#[inline]
+fn format_try_send_error<T>(e: &TrySendError<T>, f: &mut fmt::Formatter) -> fmt::Result {
+    match e {
+        &TrySendError::Io(ref io_err) => write!(f, "{}", io_err),
+        &TrySendError::Full(..) => write!(f, "Full"),
+        &TrySendError::Disconnected(..) => write!(f, "Disconnected"),
+    }
+}
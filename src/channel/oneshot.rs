@@ -0,0 +1,161 @@
+//! Single value channel where the receiver can be registered with `Poll`.
+//!
+//! The value is stored inline next to a single readiness node, so creating a
+//! oneshot channel costs one `Arc` and one `Registration`.
+
+use {io, Ready, Poll, PollOpt, Registration, SetReadiness, Token};
+use event::Evented;
+use std::cell::UnsafeCell;
+use std::sync::{mpsc, Arc};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Creates a new oneshot channel.
+///
+/// Once registered, the `OneshotReceiver` becomes readable exactly once:
+/// either when the value is sent, or with `hup` readiness when the sender is
+/// dropped without sending.
+pub fn oneshot<T>() -> (OneshotSender<T>, OneshotReceiver<T>) {
+    let (registration, set_readiness) = Registration::new2();
+
+    let inner = Arc::new(Inner {
+        state: AtomicUsize::new(0),
+        value: UnsafeCell::new(None),
+        set_readiness: set_readiness,
+    });
+
+    let tx = OneshotSender {
+        inner: inner.clone(),
+    };
+
+    let rx = OneshotReceiver {
+        inner: inner,
+        registration: registration,
+    };
+
+    (tx, rx)
+}
+
+pub struct OneshotSender<T> {
+    inner: Arc<Inner<T>>,
+}
+
+pub struct OneshotReceiver<T> {
+    inner: Arc<Inner<T>>,
+    registration: Registration,
+}
+
+struct Inner<T> {
+    // Combination of the flags below
+    state: AtomicUsize,
+    // Written by the sender before setting `SENT`
+    value: UnsafeCell<Option<T>>,
+    set_readiness: SetReadiness,
+}
+
+// The value has been sent
+const SENT: usize = 1;
+// The sender was dropped without sending
+const CLOSED: usize = 2;
+// The value has been taken by the receiver
+const TAKEN: usize = 4;
+// The sender has finished setting readiness
+const NOTIFIED: usize = 8;
+// The receiver has been dropped
+const RX_DROPPED: usize = 16;
+
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+impl<T> OneshotSender<T> {
+    /// Sends the value, returning it back if the receiver has been dropped.
+    pub fn send(self, t: T) -> Result<(), T> {
+        if self.inner.state.load(Ordering::Acquire) & RX_DROPPED != 0 {
+            return Err(t);
+        }
+
+        unsafe { *self.inner.value.get() = Some(t); }
+
+        let prev = self.inner.state.fetch_or(SENT, Ordering::AcqRel);
+
+        if prev & RX_DROPPED != 0 {
+            // The receiver will never look at the value
+            return Err(unsafe { (*self.inner.value.get()).take().unwrap() });
+        }
+
+        let _ = self.inner.set_readiness.set_readiness(Ready::readable());
+
+        // The receiver cannot unset readiness until it has been set, so if it
+        // already took the value, it is up to the sender to unset it.
+        let prev = self.inner.state.fetch_or(NOTIFIED, Ordering::AcqRel);
+
+        if prev & TAKEN != 0 {
+            let _ = self.inner.set_readiness.set_readiness(Ready::empty());
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Drop for OneshotSender<T> {
+    fn drop(&mut self) {
+        if self.inner.state.load(Ordering::Acquire) & SENT != 0 {
+            return;
+        }
+
+        self.inner.state.fetch_or(CLOSED, Ordering::AcqRel);
+        let _ = self.inner.set_readiness.set_readiness(Ready::readable() | Ready::hup());
+    }
+}
+
+impl<T> OneshotReceiver<T> {
+    /// Attempts to receive the value.
+    ///
+    /// Returns `Disconnected` if the sender was dropped without sending, or
+    /// if the value has already been received.
+    pub fn try_recv(&self) -> Result<T, mpsc::TryRecvError> {
+        let state = self.inner.state.load(Ordering::Acquire);
+
+        if state & (SENT | TAKEN) == SENT {
+            let prev = self.inner.state.fetch_or(TAKEN, Ordering::AcqRel);
+
+            if prev & TAKEN != 0 {
+                return Err(mpsc::TryRecvError::Disconnected);
+            }
+
+            let t = unsafe { (*self.inner.value.get()).take().unwrap() };
+
+            if prev & NOTIFIED != 0 {
+                let _ = self.inner.set_readiness.set_readiness(Ready::empty());
+            }
+
+            return Ok(t);
+        }
+
+        if state & (CLOSED | TAKEN) != 0 {
+            return Err(mpsc::TryRecvError::Disconnected);
+        }
+
+        Err(mpsc::TryRecvError::Empty)
+    }
+}
+
+impl<T> Drop for OneshotReceiver<T> {
+    fn drop(&mut self) {
+        self.inner.state.fetch_or(RX_DROPPED, Ordering::AcqRel);
+    }
+}
+
+impl<T> Evented for OneshotReceiver<T> {
+    fn register(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+        // Like the system selector, always report hup
+        self.registration.register(poll, token, interest | Ready::hup(), opts)
+    }
+
+    fn reregister(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+        self.registration.reregister(poll, token, interest | Ready::hup(), opts)
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        self.registration.deregister(poll)
+    }
+}
@@ -0,0 +1,301 @@
+//! Lock-free MPSC queue backing `channel::channel`.
+//!
+//! Senders push nodes onto an atomic stack. The receiver takes the whole stack
+//! at once and reverses it into a local FIFO list, which it then pops from
+//! without any further synchronization. Since the stack is only ever taken as
+//! a whole, pushes are not subject to ABA.
+//!
+//! Pushing onto an empty stack is what transitions the queue from empty to
+//! non-empty, so that is the only time a sender sets readiness. The receiver
+//! recomputes readiness whenever its local list runs dry.
+
+use {io, Ready, Poll, PollOpt, Registration, SetReadiness, Token};
+use event::Evented;
+use lazycell::{LazyCell, AtomicLazyCell};
+use std::cell::Cell;
+use std::ptr;
+use std::sync::{mpsc, Arc};
+use std::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+use super::SendError;
+
+pub fn queue<T>() -> (Sender<T>, Receiver<T>) {
+    let inner = Arc::new(Inner {
+        stack: AtomicPtr::new(ptr::null_mut()),
+        senders: AtomicUsize::new(1),
+        receiver_dropped: AtomicBool::new(false),
+        set_readiness: AtomicLazyCell::new(),
+    });
+
+    let tx = Sender {
+        inner: inner.clone(),
+    };
+
+    let rx = Receiver {
+        inner: inner,
+        head: Cell::new(ptr::null_mut()),
+        registration: LazyCell::new(),
+    };
+
+    (tx, rx)
+}
+
+pub struct Sender<T> {
+    inner: Arc<Inner<T>>,
+}
+
+pub struct Receiver<T> {
+    inner: Arc<Inner<T>>,
+    // Nodes taken from the stack, in FIFO order
+    head: Cell<*mut Node<T>>,
+    registration: LazyCell<Registration>,
+}
+
+struct Inner<T> {
+    // Nodes pushed by senders, most recent first
+    stack: AtomicPtr<Node<T>>,
+    // The number of sender handles
+    senders: AtomicUsize,
+    // Set when the receiver handle is dropped
+    receiver_dropped: AtomicBool,
+    // The set readiness handle
+    set_readiness: AtomicLazyCell<SetReadiness>,
+}
+
+struct Node<T> {
+    value: T,
+    next: *mut Node<T>,
+}
+
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send> Sync for Inner<T> {}
+unsafe impl<T: Send> Send for Receiver<T> {}
+
+impl<T> Sender<T> {
+    pub fn send(&self, t: T) -> Result<(), SendError<T>> {
+        if self.inner.receiver_dropped.load(Ordering::Acquire) {
+            return Err(SendError::Disconnected(t));
+        }
+
+        let node = Box::into_raw(Box::new(Node {
+            value: t,
+            next: ptr::null_mut(),
+        }));
+
+        let mut head = self.inner.stack.load(Ordering::Relaxed);
+
+        loop {
+            unsafe { (*node).next = head; }
+
+            let actual = self.inner.stack.compare_and_swap(head, node, Ordering::AcqRel);
+
+            if actual == head {
+                break;
+            }
+
+            head = actual;
+        }
+
+        if head.is_null() {
+            // The queue went from empty to non-empty, toggle readiness to
+            // readable
+            if let Some(set_readiness) = self.inner.set_readiness.borrow() {
+                set_readiness.set_readiness(Ready::readable())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.inner.senders.fetch_add(1, Ordering::Relaxed);
+        Sender { inner: self.inner.clone() }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if self.inner.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            if let Some(set_readiness) = self.inner.set_readiness.borrow() {
+                let _ = set_readiness.set_readiness(Ready::readable() | Ready::hup());
+            }
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    pub fn try_recv(&self) -> Result<T, mpsc::TryRecvError> {
+        match self.pop() {
+            Some(t) => {
+                if self.head.get().is_null() {
+                    let _ = self.inner.update_readiness();
+                }
+
+                Ok(t)
+            }
+            None => {
+                let _ = self.inner.update_readiness();
+                Err(self.empty_error())
+            }
+        }
+    }
+
+    pub fn try_recv_many(&self, dst: &mut Vec<T>, max: usize) -> Result<usize, mpsc::TryRecvError> {
+        let mut n = 0;
+
+        while n < max {
+            match self.pop() {
+                Some(t) => {
+                    dst.push(t);
+                    n += 1;
+                }
+                None => break,
+            }
+        }
+
+        if self.head.get().is_null() {
+            let _ = self.inner.update_readiness();
+        }
+
+        if n == 0 && max > 0 {
+            return Err(self.empty_error());
+        }
+
+        Ok(n)
+    }
+
+    fn pop(&self) -> Option<T> {
+        let mut head = self.head.get();
+
+        if head.is_null() {
+            head = self.take_stack();
+
+            if head.is_null() {
+                return None;
+            }
+        }
+
+        let node = unsafe { Box::from_raw(head) };
+        self.head.set(node.next);
+
+        Some(node.value)
+    }
+
+    // Takes all nodes pushed so far and returns them in FIFO order
+    fn take_stack(&self) -> *mut Node<T> {
+        let mut curr = self.inner.stack.swap(ptr::null_mut(), Ordering::Acquire);
+        let mut prev = ptr::null_mut();
+
+        while !curr.is_null() {
+            unsafe {
+                let next = (*curr).next;
+                (*curr).next = prev;
+                prev = curr;
+                curr = next;
+            }
+        }
+
+        prev
+    }
+
+    // Called when nothing could be popped
+    fn empty_error(&self) -> mpsc::TryRecvError {
+        if self.inner.senders.load(Ordering::Acquire) == 0 {
+            // All values sent before the last sender was dropped are visible
+            // now, so if the stack is still empty the channel is done.
+            if self.inner.stack.load(Ordering::Acquire).is_null() {
+                return mpsc::TryRecvError::Disconnected;
+            }
+        }
+
+        mpsc::TryRecvError::Empty
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.inner.receiver_dropped.store(true, Ordering::Release);
+
+        while self.pop().is_some() {}
+    }
+}
+
+impl<T> Evented for Receiver<T> {
+    fn register(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+        if self.registration.borrow().is_some() {
+            return Err(io::Error::new(io::ErrorKind::Other, "receiver already registered"));
+        }
+
+        // Like the system selector, always report hup
+        let (registration, set_readiness) = Registration::new(poll, token, interest | Ready::hup(), opts);
+
+        self.registration.fill(registration).ok().expect("unexpected state encountered");
+        self.inner.set_readiness.fill(set_readiness).ok().expect("unexpected state encountered");
+
+        if self.head.get().is_null() {
+            self.inner.update_readiness()
+        } else {
+            // Values taken from the stack are still waiting to be received
+            self.inner.set_readiness.borrow().unwrap().set_readiness(Ready::readable())
+        }
+    }
+
+    fn reregister(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+        match self.registration.borrow() {
+            Some(registration) => registration.update(poll, token, interest | Ready::hup(), opts),
+            None => Err(io::Error::new(io::ErrorKind::Other, "receiver not registered")),
+        }
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        match self.registration.borrow() {
+            Some(registration) => registration.deregister(poll),
+            None => Err(io::Error::new(io::ErrorKind::Other, "receiver not registered")),
+        }
+    }
+}
+
+impl<T> Inner<T> {
+    // Sets the receiver's readiness from the state of the stack. Must only be
+    // called when the receiver's local list is empty.
+    fn update_readiness(&self) -> io::Result<()> {
+        let set_readiness = match self.set_readiness.borrow() {
+            Some(set_readiness) => set_readiness,
+            None => return Ok(()),
+        };
+
+        loop {
+            let pending = !self.stack.load(Ordering::Acquire).is_null();
+            let closed = self.senders.load(Ordering::Acquire) == 0;
+
+            if closed {
+                set_readiness.set_readiness(Ready::readable() | Ready::hup())?;
+            } else if pending {
+                set_readiness.set_readiness(Ready::readable())?;
+            } else {
+                set_readiness.set_readiness(Ready::empty())?;
+            }
+
+            // If the state changed concurrently, the readiness that was just
+            // set may be stale.
+            if pending == !self.stack.load(Ordering::Acquire).is_null() &&
+                closed == (self.senders.load(Ordering::Acquire) == 0)
+            {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<T> Drop for Inner<T> {
+    fn drop(&mut self) {
+        let mut curr = *self.stack.get_mut();
+
+        while !curr.is_null() {
+            let node = unsafe { Box::from_raw(curr) };
+            curr = node.next;
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Counting semaphore where the acquiring side can be registered with `Poll`.
+//!
+//! Permits are tracked by the same counter `ctl_pair` uses for pending
+//! messages: adding permits is sending, acquiring them is receiving.
+
+use {io, Ready, Poll, PollOpt, Token};
+use event::Evented;
+
+use super::{ctl_pair, ReceiverCtl, SenderCtl};
+
+/// Counting semaphore that is readable while permits are available.
+///
+/// Permits can be added from other threads through a `SemaphoreHandle`.
+pub struct Semaphore {
+    tx: SenderCtl,
+    rx: ReceiverCtl,
+}
+
+/// Adds permits to a `Semaphore` from any thread.
+///
+/// Handles are `Send` but not `Sync`; clone one for each thread.
+#[derive(Clone)]
+pub struct SemaphoreHandle {
+    tx: SenderCtl,
+}
+
+impl Semaphore {
+    /// Creates a new semaphore with `permits` available permits.
+    pub fn new(permits: usize) -> Semaphore {
+        let (tx, rx) = ctl_pair();
+
+        // Not registered yet, so this cannot fail
+        let _ = tx.add(permits);
+
+        Semaphore {
+            tx: tx,
+            rx: rx,
+        }
+    }
+
+    /// Returns a handle that can add permits from other threads.
+    pub fn handle(&self) -> SemaphoreHandle {
+        SemaphoreHandle { tx: self.tx.clone() }
+    }
+
+    /// Adds `n` permits, making the semaphore readable if none were
+    /// available.
+    ///
+    /// Fails, adding nothing, if the number of available permits would
+    /// overflow a `usize`.
+    pub fn add_permits(&self, n: usize) -> io::Result<()> {
+        self.tx.add(n)
+    }
+
+    /// Acquires `n` permits if that many are available. Returns `false` and
+    /// acquires nothing otherwise.
+    ///
+    /// Once no permits are left, the semaphore is no longer readable.
+    pub fn try_acquire(&self, n: usize) -> io::Result<bool> {
+        self.rx.try_sub(n)
+    }
+
+    /// Returns the number of permits currently available.
+    pub fn available_permits(&self) -> usize {
+        self.rx.pending()
+    }
+}
+
+impl SemaphoreHandle {
+    /// Adds `n` permits, making the semaphore readable if none were
+    /// available.
+    ///
+    /// Fails, adding nothing, if the number of available permits would
+    /// overflow a `usize`.
+    pub fn add_permits(&self, n: usize) -> io::Result<()> {
+        self.tx.add(n)
+    }
+}
+
+impl Evented for Semaphore {
+    fn register(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+        self.rx.register(poll, token, interest, opts)
+    }
+
+    fn reregister(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
+        self.rx.reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        self.rx.deregister(poll)
+    }
+}
@@ -204,6 +204,8 @@ pub mod unix {
         EventedFd,
     };
     pub use sys::unix::UnixReady;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub use sys::unix::SplicePipe;
 }
 
 #[cfg(target_os = "fuchsia")]
@@ -0,0 +1,222 @@
+use std::{fmt, ops};
+
+/// Flags for [`TcpStream::recv_with_flags`].
+///
+/// `RecvFlags` values can be combined together using the bitwise or
+/// operator. Flags that the target platform does not support make the call
+/// fail.
+///
+/// # Examples
+///
+/// ```
+/// use mio::net::RecvFlags;
+///
+/// let flags = RecvFlags::peek() | RecvFlags::wait_all();
+///
+/// assert!(flags.is_peek());
+/// assert!(!flags.is_trunc());
+/// ```
+///
+/// [`TcpStream::recv_with_flags`]: struct.TcpStream.html#method.recv_with_flags
+#[derive(Copy, PartialEq, Eq, Clone, Default)]
+pub struct RecvFlags(u8);
+
+const PEEK: u8 = 0b001;
+const WAIT_ALL: u8 = 0b010;
+const TRUNC: u8 = 0b100;
+
+impl RecvFlags {
+    /// Returns a `RecvFlags` with no flags set.
+    #[inline]
+    pub fn empty() -> RecvFlags {
+        RecvFlags(0)
+    }
+
+    /// `MSG_PEEK`: returns data without removing it from the receive queue.
+    #[inline]
+    pub fn peek() -> RecvFlags {
+        RecvFlags(PEEK)
+    }
+
+    /// `MSG_WAITALL`: returns only once the whole buffer is filled.
+    ///
+    /// As the socket is nonblocking, this still returns early with whatever
+    /// is available, but the kernel wakes the reader less often.
+    #[inline]
+    pub fn wait_all() -> RecvFlags {
+        RecvFlags(WAIT_ALL)
+    }
+
+    /// `MSG_TRUNC`: discards the received data instead of copying it,
+    /// returning how much was discarded.
+    ///
+    /// Only supported on Linux.
+    #[inline]
+    pub fn trunc() -> RecvFlags {
+        RecvFlags(TRUNC)
+    }
+
+    /// Returns true if `MSG_PEEK` is set.
+    #[inline]
+    pub fn is_peek(&self) -> bool {
+        self.contains(RecvFlags::peek())
+    }
+
+    /// Returns true if `MSG_WAITALL` is set.
+    #[inline]
+    pub fn is_wait_all(&self) -> bool {
+        self.contains(RecvFlags::wait_all())
+    }
+
+    /// Returns true if `MSG_TRUNC` is set.
+    #[inline]
+    pub fn is_trunc(&self) -> bool {
+        self.contains(RecvFlags::trunc())
+    }
+
+    /// Returns true if `self` is a superset of `other`.
+    #[inline]
+    pub fn contains(&self, other: RecvFlags) -> bool {
+        (self.0 & other.0) == other.0
+    }
+}
+
+impl ops::BitOr for RecvFlags {
+    type Output = RecvFlags;
+
+    #[inline]
+    fn bitor(self, other: RecvFlags) -> RecvFlags {
+        RecvFlags(self.0 | other.0)
+    }
+}
+
+impl fmt::Debug for RecvFlags {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let flags = [
+            (RecvFlags::peek(), "MSG_PEEK"),
+            (RecvFlags::wait_all(), "MSG_WAITALL"),
+            (RecvFlags::trunc(), "MSG_TRUNC")];
+
+        debug_flags(fmt, flags.iter().filter(|&&(f, _)| self.contains(f)).map(|&(_, msg)| msg))
+    }
+}
+
+/// Flags for [`TcpStream::send_with_flags`].
+///
+/// `SendFlags` values can be combined together using the bitwise or
+/// operator. Flags that the target platform does not support make the call
+/// fail.
+///
+/// # Examples
+///
+/// ```
+/// use mio::net::SendFlags;
+///
+/// let flags = SendFlags::more() | SendFlags::no_signal();
+///
+/// assert!(flags.is_more());
+/// assert!(!flags.is_dont_route());
+/// ```
+///
+/// [`TcpStream::send_with_flags`]: struct.TcpStream.html#method.send_with_flags
+#[derive(Copy, PartialEq, Eq, Clone, Default)]
+pub struct SendFlags(u8);
+
+const MORE: u8 = 0b001;
+const NO_SIGNAL: u8 = 0b010;
+const DONT_ROUTE: u8 = 0b100;
+
+impl SendFlags {
+    /// Returns a `SendFlags` with no flags set.
+    #[inline]
+    pub fn empty() -> SendFlags {
+        SendFlags(0)
+    }
+
+    /// `MSG_MORE`: more data follows, so hold back partial segments as with
+    /// `TCP_CORK`, but only for this call.
+    ///
+    /// Only supported on Linux.
+    #[inline]
+    pub fn more() -> SendFlags {
+        SendFlags(MORE)
+    }
+
+    /// `MSG_NOSIGNAL`: fails with `EPIPE` instead of raising `SIGPIPE` when
+    /// the peer has closed the connection.
+    ///
+    /// Only supported on Linux.
+    #[inline]
+    pub fn no_signal() -> SendFlags {
+        SendFlags(NO_SIGNAL)
+    }
+
+    /// `MSG_DONTROUTE`: sends only to directly connected hosts, bypassing
+    /// the routing table.
+    #[inline]
+    pub fn dont_route() -> SendFlags {
+        SendFlags(DONT_ROUTE)
+    }
+
+    /// Returns true if `MSG_MORE` is set.
+    #[inline]
+    pub fn is_more(&self) -> bool {
+        self.contains(SendFlags::more())
+    }
+
+    /// Returns true if `MSG_NOSIGNAL` is set.
+    #[inline]
+    pub fn is_no_signal(&self) -> bool {
+        self.contains(SendFlags::no_signal())
+    }
+
+    /// Returns true if `MSG_DONTROUTE` is set.
+    #[inline]
+    pub fn is_dont_route(&self) -> bool {
+        self.contains(SendFlags::dont_route())
+    }
+
+    /// Returns true if `self` is a superset of `other`.
+    #[inline]
+    pub fn contains(&self, other: SendFlags) -> bool {
+        (self.0 & other.0) == other.0
+    }
+}
+
+impl ops::BitOr for SendFlags {
+    type Output = SendFlags;
+
+    #[inline]
+    fn bitor(self, other: SendFlags) -> SendFlags {
+        SendFlags(self.0 | other.0)
+    }
+}
+
+impl fmt::Debug for SendFlags {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let flags = [
+            (SendFlags::more(), "MSG_MORE"),
+            (SendFlags::no_signal(), "MSG_NOSIGNAL"),
+            (SendFlags::dont_route(), "MSG_DONTROUTE")];
+
+        debug_flags(fmt, flags.iter().filter(|&&(f, _)| self.contains(f)).map(|&(_, msg)| msg))
+    }
+}
+
+fn debug_flags<'a, I>(fmt: &mut fmt::Formatter, flags: I) -> fmt::Result
+    where I: Iterator<Item = &'a str>
+{
+    let mut one = false;
+
+    for msg in flags {
+        if one { write!(fmt, " | ")? }
+        write!(fmt, "{}", msg)?;
+        one = true
+    }
+
+    if !one {
+        fmt.write_str("(empty)")?;
+    }
+
+    Ok(())
+}
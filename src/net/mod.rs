@@ -7,8 +7,11 @@
 //!
 //! [portability guidelines]: ../struct.Poll.html#portability
 
+mod flags;
 mod tcp;
 mod udp;
 
-pub use self::tcp::{TcpListener, TcpStream};
-pub use self::udp::UdpSocket;
+pub use self::flags::{RecvFlags, SendFlags};
+pub use self::tcp::{TcpConnector, TcpListener, TcpSocket, TcpStream};
+pub use self::tcp::{Cork, TcpInfo, TcpState, ZerocopyCompletion, ZerocopyCompletions};
+pub use self::udp::{UdpSocket, UdpSocketBuilder};
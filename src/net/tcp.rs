@@ -7,16 +7,24 @@
 //!
 /// [portability guidelines]: ../struct.Poll.html#portability
 
+use std::cell::Cell;
+use std::cmp;
+use std::collections::VecDeque;
 use std::fmt;
+use std::fs::File;
 use std::io::{Read, Write};
 use std::net::{self, SocketAddr, SocketAddrV4, SocketAddrV6, Ipv4Addr, Ipv6Addr};
-use std::time::Duration;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-use net2::TcpBuilder;
+use net2::{TcpBuilder, TcpStreamExt};
+#[cfg(all(unix, not(target_os = "solaris")))]
+use net2::unix::UnixTcpBuilderExt;
 use iovec::IoVec;
 
 use {io, sys, Ready, Poll, PollOpt, Token};
 use event::Evented;
+use net::{RecvFlags, SendFlags};
 use poll::SelectorId;
 
 /*
@@ -86,20 +94,28 @@ impl TcpStream {
     /// This convenience method is available and uses the system's default
     /// options when creating a socket which is then connected. If fine-grained
     /// control over the creation of the socket is desired, you can use
-    /// `net2::TcpBuilder` to configure a socket and then pass its socket to
-    /// `TcpStream::connect_stream` to transfer ownership into mio and schedule
-    /// the connect operation.
+    /// [`TcpSocket`] to configure a socket before connecting it.
+    ///
+    /// [`TcpSocket`]: struct.TcpSocket.html
     pub fn connect(addr: &SocketAddr) -> io::Result<TcpStream> {
-        let sock = match *addr {
-            SocketAddr::V4(..) => TcpBuilder::new_v4(),
-            SocketAddr::V6(..) => TcpBuilder::new_v6(),
-        }?;
-        // Required on Windows for a future `connect_overlapped` operation to be
-        // executed successfully.
-        if cfg!(windows) {
-            sock.bind(&inaddr_any(addr))?;
-        }
-        TcpStream::connect_stream(sock.to_tcp_stream()?, addr)
+        TcpSocket::new_for_addr(addr)?.connect(addr)
+    }
+
+    /// Create a new TCP stream and issue a non-blocking connect to the
+    /// specified address, sending `data` along with the connection request
+    /// using TCP Fast Open.
+    ///
+    /// Returns the stream and the number of bytes of `data` that were
+    /// accepted. Bytes that were not accepted must be written once the stream
+    /// is writable, as with a stream returned by `connect`.
+    ///
+    /// On Linux, this uses `sendto` with `MSG_FASTOPEN`. The data is only
+    /// carried in the SYN once a Fast Open cookie for the server has been
+    /// cached from a previous connection; until then no bytes are accepted.
+    /// If Fast Open is disabled for clients, or the platform does not support
+    /// it, this behaves like `connect` and returns `0`.
+    pub fn connect_with_data(addr: &SocketAddr, data: &[u8]) -> io::Result<(TcpStream, usize)> {
+        TcpSocket::new_for_addr(addr)?.connect_with_data(addr, data)
     }
 
     /// Creates a new `TcpStream` from the pending socket inside the given
@@ -157,6 +173,20 @@ impl TcpStream {
         self.sys.local_addr()
     }
 
+    /// Returns the destination address the peer originally connected to,
+    /// before it was rewritten by an iptables `REDIRECT` or `DNAT` rule.
+    ///
+    /// This reads `SO_ORIGINAL_DST`, or `IP6T_SO_ORIGINAL_DST` for IPv6, from
+    /// the connection tracking table. For connections that were not
+    /// redirected this is the local address, and it fails if connection
+    /// tracking is not enabled. Connections intercepted with `TPROXY` keep
+    /// their original destination as the local address instead.
+    ///
+    /// Only supported on Linux.
+    pub fn original_dst(&self) -> io::Result<SocketAddr> {
+        self.sys.original_dst()
+    }
+
     /// Creates a new independently owned handle to the underlying socket.
     ///
     /// The returned `TcpStream` is a reference to the same stream that this
@@ -201,6 +231,88 @@ impl TcpStream {
         self.sys.nodelay()
     }
 
+    /// Sets the value of the `TCP_NOTSENT_LOWAT` option on this socket.
+    ///
+    /// Once set, the socket is only reported as writable while fewer than
+    /// `bytes` of already written data are waiting to be sent. This keeps
+    /// unsent data in the application, where it can still be reprioritized,
+    /// instead of in the kernel's send buffer. Zero restores the system
+    /// default.
+    ///
+    /// Supported on Linux and macOS.
+    pub fn set_notsent_lowat(&self, bytes: u32) -> io::Result<()> {
+        self.sys.set_notsent_lowat(bytes)
+    }
+
+    /// Gets the value of the `TCP_NOTSENT_LOWAT` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_notsent_lowat`][link].
+    ///
+    /// [link]: #method.set_notsent_lowat
+    pub fn notsent_lowat(&self) -> io::Result<u32> {
+        self.sys.notsent_lowat()
+    }
+
+    /// Sets the value of the `TCP_CORK` option on this socket.
+    ///
+    /// While set, partial segments are held back so that several writes can
+    /// go out as full segments. Clearing the option sends anything still
+    /// queued. The kernel sends held back data after 200ms regardless.
+    ///
+    /// See [`cork`] for a guard that clears the option when dropped.
+    ///
+    /// Only supported on Linux.
+    ///
+    /// [`cork`]: #method.cork
+    pub fn set_cork(&self, cork: bool) -> io::Result<()> {
+        self.sys.set_cork(cork)
+    }
+
+    /// Gets the value of the `TCP_CORK` option on this socket.
+    ///
+    /// For more information about this option, see [`set_cork`][link].
+    ///
+    /// [link]: #method.set_cork
+    pub fn corked(&self) -> io::Result<bool> {
+        self.sys.cork()
+    }
+
+    /// Sets the `TCP_CORK` option on this socket until the returned guard is
+    /// dropped.
+    ///
+    /// For more information about this option, see [`set_cork`][link].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use std::error::Error;
+    /// # fn try_main() -> Result<(), Box<Error>> {
+    /// use mio::net::TcpStream;
+    /// use std::io::Write;
+    ///
+    /// let mut stream = TcpStream::connect(&"127.0.0.1:8080".parse()?)?;
+    ///
+    /// {
+    ///     let _cork = stream.cork()?;
+    ///     (&stream).write_all(b"HTTP/1.1 200 OK\r\n\r\n")?;
+    ///     (&stream).write_all(b"hello")?;
+    /// }
+    /// // Both writes are flushed together once the guard is dropped
+    /// #     Ok(())
+    /// # }
+    /// #
+    /// # fn main() {
+    /// #     try_main().unwrap();
+    /// # }
+    /// ```
+    ///
+    /// [link]: #method.set_cork
+    pub fn cork(&self) -> io::Result<Cork<'_>> {
+        self.sys.set_cork(true)?;
+        Ok(Cork { stream: self })
+    }
+
     /// Sets the value of the `SO_RCVBUF` option on this socket.
     ///
     /// Changes the size of the operating system's receive buffer associated
@@ -263,6 +375,71 @@ impl TcpStream {
         self.sys.keepalive()
     }
 
+    /// Sets the value of the `TCP_KEEPINTVL` option on this socket.
+    ///
+    /// This is the time between keepalive probes once the connection has been
+    /// idle for the duration given to [`set_keepalive`]. The interval is
+    /// rounded up to whole seconds.
+    ///
+    /// Supported on Linux, FreeBSD and macOS.
+    ///
+    /// [`set_keepalive`]: #method.set_keepalive
+    pub fn set_keepalive_interval(&self, interval: Duration) -> io::Result<()> {
+        self.sys.set_keepalive_interval(interval)
+    }
+
+    /// Gets the value of the `TCP_KEEPINTVL` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_keepalive_interval`][link].
+    ///
+    /// [link]: #method.set_keepalive_interval
+    pub fn keepalive_interval(&self) -> io::Result<Duration> {
+        self.sys.keepalive_interval()
+    }
+
+    /// Sets the value of the `TCP_KEEPCNT` option on this socket.
+    ///
+    /// This is the number of unanswered keepalive probes after which the
+    /// connection is dropped.
+    ///
+    /// Supported on Linux, FreeBSD and macOS.
+    pub fn set_keepalive_retries(&self, retries: u32) -> io::Result<()> {
+        self.sys.set_keepalive_retries(retries)
+    }
+
+    /// Gets the value of the `TCP_KEEPCNT` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_keepalive_retries`][link].
+    ///
+    /// [link]: #method.set_keepalive_retries
+    pub fn keepalive_retries(&self) -> io::Result<u32> {
+        self.sys.keepalive_retries()
+    }
+
+    /// Sets the value of the `TCP_USER_TIMEOUT` option on this socket.
+    ///
+    /// This is the maximum time that transmitted data may remain
+    /// unacknowledged before the connection is dropped. It also bounds how
+    /// long keepalive probes may go unanswered. `None` restores the system
+    /// default.
+    ///
+    /// Only supported on Linux.
+    pub fn set_user_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.sys.set_user_timeout(timeout)
+    }
+
+    /// Gets the value of the `TCP_USER_TIMEOUT` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_user_timeout`][link].
+    ///
+    /// [link]: #method.set_user_timeout
+    pub fn user_timeout(&self) -> io::Result<Option<Duration>> {
+        self.sys.user_timeout()
+    }
+
     /// Sets the value for the `IP_TTL` option on this socket.
     ///
     /// This value sets the time-to-live field that is used in every packet sent
@@ -280,6 +457,97 @@ impl TcpStream {
         self.sys.ttl()
     }
 
+    /// Sets the value of the `IP_TOS` option on this socket.
+    ///
+    /// This value sets the type-of-service field of IPv4 packets sent from
+    /// this socket, whose upper six bits are the DSCP traffic class.
+    ///
+    /// Only supported on Linux.
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        self.sys.set_tos(tos)
+    }
+
+    /// Gets the value of the `IP_TOS` option on this socket.
+    ///
+    /// For more information about this option, see [`set_tos`][link].
+    ///
+    /// [link]: #method.set_tos
+    pub fn tos(&self) -> io::Result<u32> {
+        self.sys.tos()
+    }
+
+    /// Sets the value of the `IPV6_TCLASS` option on this socket.
+    ///
+    /// This is the IPv6 counterpart of [`set_tos`], setting the traffic class
+    /// field of packets sent from this socket.
+    ///
+    /// Only supported on Linux.
+    ///
+    /// [`set_tos`]: #method.set_tos
+    pub fn set_tclass_v6(&self, tclass: u32) -> io::Result<()> {
+        self.sys.set_tclass_v6(tclass)
+    }
+
+    /// Gets the value of the `IPV6_TCLASS` option on this socket.
+    ///
+    /// For more information about this option, see [`set_tclass_v6`][link].
+    ///
+    /// [link]: #method.set_tclass_v6
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        self.sys.tclass_v6()
+    }
+
+    /// Sets the value of the `SO_MARK` option on this socket.
+    ///
+    /// The mark is attached to every packet sent from this socket, where
+    /// routing rules and packet filters can match on it. Setting it requires
+    /// the `CAP_NET_ADMIN` capability.
+    ///
+    /// Only supported on Linux.
+    pub fn set_mark(&self, mark: u32) -> io::Result<()> {
+        self.sys.set_mark(mark)
+    }
+
+    /// Gets the value of the `SO_MARK` option on this socket.
+    ///
+    /// For more information about this option, see [`set_mark`][link].
+    ///
+    /// [link]: #method.set_mark
+    pub fn mark(&self) -> io::Result<u32> {
+        self.sys.mark()
+    }
+
+    /// Sets the value of the `SO_BINDTODEVICE` option on this socket.
+    ///
+    /// Once bound to a network interface, given by name such as `b"eth0"`,
+    /// the socket only sends and receives packets through that interface.
+    /// `None` removes the binding. Before Linux 5.7 this requires the
+    /// `CAP_NET_RAW` capability.
+    ///
+    /// Only supported on Linux.
+    pub fn bind_device(&self, interface: Option<&[u8]>) -> io::Result<()> {
+        self.sys.bind_device(interface)
+    }
+
+    /// Sets the value of the `IP_FREEBIND` option on this socket.
+    ///
+    /// If set, the socket can be bound to an address that is not, or not
+    /// yet, assigned to a local interface.
+    ///
+    /// Only supported on Linux.
+    pub fn set_freebind(&self, freebind: bool) -> io::Result<()> {
+        self.sys.set_freebind(freebind)
+    }
+
+    /// Gets the value of the `IP_FREEBIND` option on this socket.
+    ///
+    /// For more information about this option, see [`set_freebind`][link].
+    ///
+    /// [link]: #method.set_freebind
+    pub fn freebind(&self) -> io::Result<bool> {
+        self.sys.freebind()
+    }
+
     /// Sets the value for the `IPV6_V6ONLY` option on this socket.
     ///
     /// If this is set to `true` then the socket is restricted to sending and
@@ -352,6 +620,106 @@ impl TcpStream {
         self.sys.peek(buf)
     }
 
+    /// Sends up to `len` bytes of `file`, starting at `offset`, without
+    /// copying them through user space.
+    ///
+    /// The file's own position is not changed. Returns the number of bytes
+    /// sent, which is `0` once `offset` reaches the end of the file. If the
+    /// socket is not currently writable then a "would block" error is
+    /// returned.
+    ///
+    /// On Linux this corresponds to the `sendfile` syscall. Other platforms
+    /// return an error.
+    pub fn send_file(&self, file: &File, offset: u64, len: usize) -> io::Result<usize> {
+        self.sys.send_file(file, offset, len)
+    }
+
+    /// Sets the value of the `SO_ZEROCOPY` option on this socket.
+    ///
+    /// This must be enabled before [`send_zerocopy`] can avoid copying.
+    ///
+    /// Only supported on Linux.
+    ///
+    /// [`send_zerocopy`]: #method.send_zerocopy
+    pub fn set_zerocopy(&self, zerocopy: bool) -> io::Result<()> {
+        self.sys.set_zerocopy(zerocopy)
+    }
+
+    /// Gets the value of the `SO_ZEROCOPY` option on this socket.
+    ///
+    /// For more information about this option, see [`set_zerocopy`][link].
+    ///
+    /// [link]: #method.set_zerocopy
+    pub fn zerocopy(&self) -> io::Result<bool> {
+        self.sys.zerocopy()
+    }
+
+    /// Sends data without copying it into the kernel, using `MSG_ZEROCOPY`.
+    ///
+    /// The kernel keeps referencing `buf` after this returns, so it must not
+    /// be modified or freed until the send has completed. Every call that
+    /// sends data is numbered, starting from `0` for the first one on the
+    /// socket. Once sends have completed, the socket reports error readiness
+    /// (`UnixReady::error()`), and the numbers of the completed sends can be
+    /// read with [`zerocopy_completions`].
+    ///
+    /// Returns the number of bytes sent. If the socket is not currently
+    /// writable then a "would block" error is returned.
+    ///
+    /// Only supported on Linux, and requires [`set_zerocopy`].
+    ///
+    /// [`zerocopy_completions`]: #method.zerocopy_completions
+    /// [`set_zerocopy`]: #method.set_zerocopy
+    pub fn send_zerocopy(&self, buf: &[u8]) -> io::Result<usize> {
+        self.sys.send_zerocopy(buf)
+    }
+
+    /// Returns an iterator over the notifications for completed
+    /// [`send_zerocopy`] calls.
+    ///
+    /// The iterator reads notifications from the socket error queue until it
+    /// is empty.
+    ///
+    /// [`send_zerocopy`]: #method.send_zerocopy
+    pub fn zerocopy_completions(&self) -> ZerocopyCompletions<'_> {
+        ZerocopyCompletions { stream: self }
+    }
+
+    /// Returns statistics about this connection, read from the `TCP_INFO`
+    /// option.
+    ///
+    /// Only supported on Linux.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn tcp_info(&self) -> io::Result<TcpInfo> {
+        self.sys.tcp_info().map(|info| {
+            TcpInfo {
+                state: TcpState::from_raw(info.tcpi_state),
+                retransmits: info.tcpi_retransmits as u32,
+                total_retransmits: info.tcpi_total_retrans,
+                snd_mss: info.tcpi_snd_mss,
+                unacked: info.tcpi_unacked,
+                lost: info.tcpi_lost,
+                rtt: micros(info.tcpi_rtt),
+                rtt_var: micros(info.tcpi_rttvar),
+                min_rtt: micros(info.tcpi_min_rtt),
+                snd_cwnd: info.tcpi_snd_cwnd,
+                bytes_acked: info.tcpi_bytes_acked,
+                bytes_received: info.tcpi_bytes_received,
+                notsent_bytes: info.tcpi_notsent_bytes,
+                delivery_rate: info.tcpi_delivery_rate,
+            }
+        })
+    }
+
+    /// Returns statistics about this connection, read from the `TCP_INFO`
+    /// option.
+    ///
+    /// Only supported on Linux.
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn tcp_info(&self) -> io::Result<TcpInfo> {
+        Err(sys::unsupported())
+    }
+
     /// Read in a list of buffers all at once.
     ///
     /// This operation will attempt to read bytes from this socket and place
@@ -387,6 +755,115 @@ impl TcpStream {
     pub fn write_bufs(&self, bufs: &[&IoVec]) -> io::Result<usize> {
         self.sys.writev(bufs)
     }
+
+    /// Receives data on the socket with the given flags, returning the
+    /// number of bytes read.
+    ///
+    /// Like `read`, this returns a "would block" error if no data is
+    /// available, and does not block. Flags that the platform does not
+    /// support make the call fail without reading anything.
+    ///
+    /// On Unix this corresponds to the `recv` syscall. Not supported on
+    /// Windows.
+    pub fn recv_with_flags(&self, buf: &mut [u8], flags: RecvFlags) -> io::Result<usize> {
+        self.sys.recv_with_flags(buf, flags)
+    }
+
+    /// Sends data on the socket with the given flags, returning the number
+    /// of bytes written.
+    ///
+    /// Like `write`, this returns a "would block" error if the socket is not
+    /// currently writable, and does not block. Flags that the platform does
+    /// not support make the call fail without writing anything.
+    ///
+    /// On Unix this corresponds to the `send` syscall. Not supported on
+    /// Windows.
+    pub fn send_with_flags(&self, buf: &[u8], flags: SendFlags) -> io::Result<usize> {
+        self.sys.send_with_flags(buf, flags)
+    }
+
+    /// Reads into a list of buffers with the given flags.
+    ///
+    /// This is [`read_bufs`] with the flags of [`recv_with_flags`].
+    ///
+    /// On Unix this corresponds to the `recvmsg` syscall. Not supported on
+    /// Windows.
+    ///
+    /// [`read_bufs`]: #method.read_bufs
+    /// [`recv_with_flags`]: #method.recv_with_flags
+    pub fn recv_bufs_with_flags(&self, bufs: &mut [&mut IoVec], flags: RecvFlags)
+                                -> io::Result<usize> {
+        self.sys.recv_bufs_with_flags(bufs, flags)
+    }
+
+    /// Writes a list of buffers with the given flags.
+    ///
+    /// This is [`write_bufs`] with the flags of [`send_with_flags`].
+    ///
+    /// On Unix this corresponds to the `sendmsg` syscall. Not supported on
+    /// Windows.
+    ///
+    /// [`write_bufs`]: #method.write_bufs
+    /// [`send_with_flags`]: #method.send_with_flags
+    pub fn send_bufs_with_flags(&self, bufs: &[&IoVec], flags: SendFlags) -> io::Result<usize> {
+        self.sys.send_bufs_with_flags(bufs, flags)
+    }
+
+    /// Sends a single byte of urgent (out-of-band) data.
+    ///
+    /// The byte is sent in order with the regular data, but the peer is
+    /// notified of it straight away. A peer registered for
+    /// [`UnixReady::priority`] gets a priority event, and can read the byte
+    /// with [`recv_oob`] ahead of any regular data that precedes it.
+    ///
+    /// Not supported on Windows.
+    ///
+    /// [`UnixReady::priority`]: ../unix/struct.UnixReady.html#method.priority
+    /// [`recv_oob`]: #method.recv_oob
+    pub fn send_oob(&self, byte: u8) -> io::Result<()> {
+        self.sys.send_oob(byte)
+    }
+
+    /// Receives the pending byte of urgent (out-of-band) data.
+    ///
+    /// Returns a "would block" error if the byte has been signalled but has
+    /// not arrived yet. Fails with `EINVAL` if no urgent data is pending, or
+    /// if `SO_OOBINLINE` is set.
+    ///
+    /// Only the last urgent byte is kept, so one sent before the previous one
+    /// was received replaces it.
+    ///
+    /// Not supported on Windows.
+    pub fn recv_oob(&self) -> io::Result<u8> {
+        self.sys.recv_oob()
+    }
+
+    /// Sets the value of the `SO_OOBINLINE` option on this socket.
+    ///
+    /// If set, urgent data is left in the regular data stream, to be read
+    /// with `read`, instead of being received with [`recv_oob`]. A priority
+    /// event is still raised when it arrives.
+    ///
+    /// Not supported on Windows.
+    ///
+    /// [`recv_oob`]: #method.recv_oob
+    pub fn set_oobinline(&self, oobinline: bool) -> io::Result<()> {
+        self.sys.set_oobinline(oobinline)
+    }
+
+    /// Gets the value of the `SO_OOBINLINE` option on this socket.
+    ///
+    /// For more information about this option, see [`set_oobinline`][link].
+    ///
+    /// [link]: #method.set_oobinline
+    pub fn oobinline(&self) -> io::Result<bool> {
+        self.sys.oobinline()
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn micros(us: u32) -> Duration {
+    Duration::new((us / 1_000_000) as u64, (us % 1_000_000) * 1_000)
 }
 
 fn inaddr_any(other: &SocketAddr) -> SocketAddr {
@@ -459,6 +936,235 @@ impl fmt::Debug for TcpStream {
     }
 }
 
+/// Guard returned by [`TcpStream::cork`].
+///
+/// Clears the `TCP_CORK` option when dropped, sending any queued partial
+/// segment.
+///
+/// [`TcpStream::cork`]: struct.TcpStream.html#method.cork
+#[derive(Debug)]
+pub struct Cork<'a> {
+    stream: &'a TcpStream,
+}
+
+impl<'a> Drop for Cork<'a> {
+    fn drop(&mut self) {
+        let _ = self.stream.sys.set_cork(false);
+    }
+}
+
+/// Connection statistics returned by [`TcpStream::tcp_info`].
+///
+/// Fields that the running kernel does not report are zero.
+///
+/// [`TcpStream::tcp_info`]: struct.TcpStream.html#method.tcp_info
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TcpInfo {
+    state: TcpState,
+    retransmits: u32,
+    total_retransmits: u32,
+    snd_mss: u32,
+    unacked: u32,
+    lost: u32,
+    rtt: Duration,
+    rtt_var: Duration,
+    min_rtt: Duration,
+    snd_cwnd: u32,
+    bytes_acked: u64,
+    bytes_received: u64,
+    notsent_bytes: u32,
+    delivery_rate: u64,
+}
+
+impl TcpInfo {
+    /// Returns the state of the connection.
+    pub fn state(&self) -> TcpState {
+        self.state
+    }
+
+    /// Returns the number of consecutive retransmissions of the segment
+    /// currently awaiting acknowledgement.
+    pub fn retransmits(&self) -> u32 {
+        self.retransmits
+    }
+
+    /// Returns the total number of retransmitted segments.
+    pub fn total_retransmits(&self) -> u32 {
+        self.total_retransmits
+    }
+
+    /// Returns the maximum segment size for sending.
+    pub fn snd_mss(&self) -> u32 {
+        self.snd_mss
+    }
+
+    /// Returns the number of segments sent but not yet acknowledged.
+    pub fn unacked(&self) -> u32 {
+        self.unacked
+    }
+
+    /// Returns the number of segments considered lost.
+    pub fn lost(&self) -> u32 {
+        self.lost
+    }
+
+    /// Returns the smoothed round trip time.
+    pub fn rtt(&self) -> Duration {
+        self.rtt
+    }
+
+    /// Returns the round trip time variance.
+    pub fn rtt_var(&self) -> Duration {
+        self.rtt_var
+    }
+
+    /// Returns the lowest round trip time observed.
+    pub fn min_rtt(&self) -> Duration {
+        self.min_rtt
+    }
+
+    /// Returns the congestion window, in segments.
+    pub fn snd_cwnd(&self) -> u32 {
+        self.snd_cwnd
+    }
+
+    /// Returns the number of bytes acknowledged by the peer.
+    pub fn bytes_acked(&self) -> u64 {
+        self.bytes_acked
+    }
+
+    /// Returns the number of bytes received from the peer.
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+
+    /// Returns the number of bytes written to the socket but not sent yet.
+    pub fn notsent_bytes(&self) -> u32 {
+        self.notsent_bytes
+    }
+
+    /// Returns the most recent delivery rate estimate, in bytes per second.
+    pub fn delivery_rate(&self) -> u64 {
+        self.delivery_rate
+    }
+}
+
+/// The state of a TCP connection, as reported by [`TcpInfo::state`].
+///
+/// [`TcpInfo::state`]: struct.TcpInfo.html#method.state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpState {
+    /// The connection is open.
+    Established,
+    /// A connection request was sent.
+    SynSent,
+    /// A connection request was received and answered.
+    SynRecv,
+    /// The local side closed, waiting for its FIN to be acknowledged.
+    FinWait1,
+    /// The local side closed, waiting for the peer to close.
+    FinWait2,
+    /// Both sides closed, waiting for delayed segments to expire.
+    TimeWait,
+    /// The connection is closed.
+    Close,
+    /// The peer closed, waiting for the local side to close.
+    CloseWait,
+    /// Both sides closed, waiting for the local FIN to be acknowledged.
+    LastAck,
+    /// The socket is listening.
+    Listen,
+    /// Both sides closed simultaneously.
+    Closing,
+    /// A state not known to this version of mio.
+    Unknown(u8),
+}
+
+impl TcpState {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn from_raw(state: u8) -> TcpState {
+        match state {
+            1 => TcpState::Established,
+            2 => TcpState::SynSent,
+            3 => TcpState::SynRecv,
+            4 => TcpState::FinWait1,
+            5 => TcpState::FinWait2,
+            6 => TcpState::TimeWait,
+            7 => TcpState::Close,
+            8 => TcpState::CloseWait,
+            9 => TcpState::LastAck,
+            10 => TcpState::Listen,
+            11 => TcpState::Closing,
+            n => TcpState::Unknown(n),
+        }
+    }
+}
+
+/// A range of completed [`TcpStream::send_zerocopy`] calls.
+///
+/// [`TcpStream::send_zerocopy`]: struct.TcpStream.html#method.send_zerocopy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZerocopyCompletion {
+    start: u32,
+    end: u32,
+    copied: bool,
+}
+
+impl ZerocopyCompletion {
+    /// Returns the number of the first completed send.
+    pub fn start(&self) -> u32 {
+        self.start
+    }
+
+    /// Returns the number of the last completed send, inclusive.
+    pub fn end(&self) -> u32 {
+        self.end
+    }
+
+    /// Returns `true` if send number `n` is part of this range, meaning its
+    /// buffer may be reused.
+    pub fn contains(&self, n: u32) -> bool {
+        // Send numbers wrap around
+        n.wrapping_sub(self.start) <= self.end.wrapping_sub(self.start)
+    }
+
+    /// Returns `true` if the kernel copied the data instead of sending it in
+    /// place, for example because the route does not support it.
+    ///
+    /// When this keeps happening, sending without zerocopy is cheaper.
+    pub fn copied(&self) -> bool {
+        self.copied
+    }
+}
+
+/// Iterator over [`ZerocopyCompletion`]s, returned by
+/// [`TcpStream::zerocopy_completions`].
+///
+/// [`ZerocopyCompletion`]: struct.ZerocopyCompletion.html
+/// [`TcpStream::zerocopy_completions`]: struct.TcpStream.html#method.zerocopy_completions
+#[derive(Debug)]
+pub struct ZerocopyCompletions<'a> {
+    stream: &'a TcpStream,
+}
+
+impl<'a> Iterator for ZerocopyCompletions<'a> {
+    type Item = io::Result<ZerocopyCompletion>;
+
+    fn next(&mut self) -> Option<io::Result<ZerocopyCompletion>> {
+        match self.stream.sys.zerocopy_completion() {
+            Ok((start, end, copied)) => {
+                Some(Ok(ZerocopyCompletion {
+                    start: start,
+                    end: end,
+                    copied: copied,
+                }))
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
 /*
  *
  * ===== TcpListener =====
@@ -498,6 +1204,16 @@ impl fmt::Debug for TcpStream {
 pub struct TcpListener {
     sys: sys::TcpListener,
     selector_id: SelectorId,
+    accept_state: Mutex<AcceptState>,
+}
+
+#[derive(Debug, Default)]
+struct AcceptState {
+    // Given up to shed a connection when out of descriptors
+    reserve: Option<File>,
+    // The registration requested by the user, restored on resume
+    registration: Option<(Token, Ready, PollOpt)>,
+    paused: bool,
 }
 
 impl TcpListener {
@@ -512,38 +1228,80 @@ impl TcpListener {
     /// 4. Call `listen` on the socket to prepare it to receive new connections.
     ///
     /// If fine-grained control over the binding and listening process for a
-    /// socket is desired then the `net2::TcpBuilder` methods can be used in
-    /// combination with the `TcpListener::from_listener` method to transfer
-    /// ownership into mio.
+    /// socket is desired then [`TcpSocket`] can be used instead, for example
+    /// to choose the listen backlog.
+    ///
+    /// [`TcpSocket`]: struct.TcpSocket.html
     pub fn bind(addr: &SocketAddr) -> io::Result<TcpListener> {
         // Create the socket
-        let sock = match *addr {
-            SocketAddr::V4(..) => TcpBuilder::new_v4(),
-            SocketAddr::V6(..) => TcpBuilder::new_v6(),
-        }?;
+        let sock = TcpSocket::new_for_addr(addr)?;
 
         // Set SO_REUSEADDR, but only on Unix (mirrors what libstd does)
         if cfg!(unix) {
-            sock.reuse_address(true)?;
+            sock.set_reuseaddr(true)?;
         }
 
         // Bind the socket
         sock.bind(addr)?;
 
         // listen
-        let listener = sock.listen(1024)?;
-        Ok(TcpListener {
-            sys: sys::TcpListener::new(listener)?,
-            selector_id: SelectorId::new(),
-        })
-    }
-
-    #[deprecated(since = "0.6.13", note = "use from_std instead")]
-    #[cfg(feature = "with-deprecated")]
-    #[doc(hidden)]
-    pub fn from_listener(listener: net::TcpListener, _: &SocketAddr)
-                         -> io::Result<TcpListener> {
-        TcpListener::from_std(listener)
+        sock.listen(1024)
+    }
+
+    /// Binds `count` listeners to the same address with `SO_REUSEPORT`, for
+    /// example one per thread, each with its own `Poll`.
+    ///
+    /// The kernel spreads incoming connections across the listeners. If the
+    /// port of `addr` is zero, all listeners share the port picked for the
+    /// first one. Use [`set_cpu_steering`] to control which listener gets
+    /// each connection.
+    ///
+    /// Only available on Unix.
+    ///
+    /// [`set_cpu_steering`]: #method.set_cpu_steering
+    #[cfg(all(unix, not(target_os = "solaris")))]
+    pub fn bind_reuseport(addr: &SocketAddr, count: usize) -> io::Result<Vec<TcpListener>> {
+        let mut listeners = Vec::with_capacity(count);
+        let mut addr = *addr;
+
+        for _ in 0..count {
+            let sock = TcpSocket::new_for_addr(&addr)?;
+            sock.set_reuseaddr(true)?;
+            sock.set_reuseport(true)?;
+            sock.bind(&addr)?;
+
+            let listener = sock.listen(1024)?;
+            addr = listener.local_addr()?;
+            listeners.push(listener);
+        }
+
+        Ok(listeners)
+    }
+
+    /// Steers new connections within this listener's `SO_REUSEPORT` group by
+    /// the CPU that received them.
+    ///
+    /// This attaches a classic BPF program with `SO_ATTACH_REUSEPORT_CBPF`
+    /// that sends each connection to the listener at index
+    /// `cpu % group_size`, where listeners are indexed in the order they were
+    /// bound, as returned by [`bind_reuseport`]. The program applies to the
+    /// whole group, so it only needs to be attached to one listener. With
+    /// one listener per CPU and each thread pinned to the CPU handling its
+    /// receive queue, connections never cross CPUs.
+    ///
+    /// Only supported on Linux.
+    ///
+    /// [`bind_reuseport`]: #method.bind_reuseport
+    pub fn set_cpu_steering(&self, group_size: usize) -> io::Result<()> {
+        self.sys.set_cpu_steering(group_size)
+    }
+
+    #[deprecated(since = "0.6.13", note = "use from_std instead")]
+    #[cfg(feature = "with-deprecated")]
+    #[doc(hidden)]
+    pub fn from_listener(listener: net::TcpListener, _: &SocketAddr)
+                         -> io::Result<TcpListener> {
+        TcpListener::from_std(listener)
     }
 
     /// Creates a new `TcpListener` from an instance of a
@@ -560,6 +1318,7 @@ impl TcpListener {
             TcpListener {
                 sys: s,
                 selector_id: SelectorId::new(),
+                accept_state: Mutex::new(AcceptState::default()),
             }
         })
     }
@@ -584,7 +1343,143 @@ impl TcpListener {
     /// *in blocking mode* which isn't bound to `mio`. This can be later then
     /// converted to a `mio` type, if necessary.
     pub fn accept_std(&self) -> io::Result<(net::TcpStream, SocketAddr)> {
-        self.sys.accept()
+        self.sys.accept().map_err(|e| self.shed(e))
+    }
+
+    /// Accepts up to `max` pending connections, appending them to `conns`.
+    ///
+    /// Returns the number of connections accepted. This drains the accept
+    /// queue with one system call per connection, on Linux using `accept4` to
+    /// get sockets that are already nonblocking, so it is cheaper than
+    /// calling [`accept`] in a loop. `conns` is only ever appended to, so
+    /// reusing the same vector across calls avoids allocating.
+    ///
+    /// If no connection is pending this returns an error with kind
+    /// `io::ErrorKind::WouldBlock`, the same as [`accept`]. An error hit
+    /// after some connections were accepted is not returned; it will be
+    /// returned by the next call instead.
+    ///
+    /// [`accept`]: #method.accept
+    pub fn accept_many(&self, conns: &mut Vec<(TcpStream, SocketAddr)>, max: usize)
+                       -> io::Result<usize> {
+        let mut n = 0;
+
+        while n < max {
+            match self.sys.accept_nonblocking() {
+                Ok((s, a)) => {
+                    conns.push((TcpStream {
+                        sys: s,
+                        selector_id: SelectorId::new(),
+                    }, a));
+                    n += 1;
+                }
+                Err(e) => {
+                    let e = self.shed(e);
+                    if n == 0 {
+                        return Err(e);
+                    }
+                    break;
+                }
+            }
+        }
+
+        Ok(n)
+    }
+
+    /// Sets whether this listener keeps a file descriptor in reserve for
+    /// when the process runs out of them.
+    ///
+    /// Normally, once the descriptor limit is reached, a pending connection
+    /// can be neither accepted nor dropped, so `accept` keeps failing with
+    /// `EMFILE` or `ENFILE` while the listener stays ready. With a reserve,
+    /// such a failure closes the reserve descriptor, accepts the pending
+    /// connection and closes it straight away, then opens a new reserve.
+    /// The error is still returned, so the caller can back off, for example
+    /// with [`pause_accepting`], while the backlog drains instead of spinning.
+    ///
+    /// [`pause_accepting`]: #method.pause_accepting
+    pub fn set_fd_reserve(&self, reserve: bool) -> io::Result<()> {
+        let mut state = self.accept_state.lock().unwrap();
+
+        if !reserve {
+            state.reserve = None;
+        } else if state.reserve.is_none() {
+            state.reserve = Some(sys::reserve_fd()?);
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether this listener keeps a file descriptor in reserve.
+    ///
+    /// For more information, see [`set_fd_reserve`][link].
+    ///
+    /// [link]: #method.set_fd_reserve
+    pub fn fd_reserve(&self) -> bool {
+        self.accept_state.lock().unwrap().reserve.is_some()
+    }
+
+    /// Stops `poll` from reporting readiness for this listener, without
+    /// deregistering it.
+    ///
+    /// This lets a server stop accepting, for example when it is out of file
+    /// descriptors or at a connection limit, while new connections wait in
+    /// the backlog. The interest and options from the last `register` or
+    /// `reregister` are kept, and restored by [`resume_accepting`]. While
+    /// paused, `reregister` only updates what will be restored.
+    ///
+    /// [`resume_accepting`]: #method.resume_accepting
+    pub fn pause_accepting(&self, poll: &Poll) -> io::Result<()> {
+        let mut state = self.accept_state.lock().unwrap();
+
+        if let Some((token, _, opts)) = state.registration {
+            self.sys.reregister(poll, token, Ready::empty(), opts)?;
+        }
+        state.paused = true;
+
+        Ok(())
+    }
+
+    /// Restores the registration that [`pause_accepting`] suspended.
+    ///
+    /// With edge-triggered registrations, connections that arrived while
+    /// paused are reported again.
+    ///
+    /// [`pause_accepting`]: #method.pause_accepting
+    pub fn resume_accepting(&self, poll: &Poll) -> io::Result<()> {
+        let mut state = self.accept_state.lock().unwrap();
+
+        if let Some((token, interest, opts)) = state.registration {
+            self.sys.reregister(poll, token, interest, opts)?;
+        }
+        state.paused = false;
+
+        Ok(())
+    }
+
+    /// Returns whether accepting is paused.
+    ///
+    /// For more information, see [`pause_accepting`][link].
+    ///
+    /// [link]: #method.pause_accepting
+    pub fn is_accepting_paused(&self) -> bool {
+        self.accept_state.lock().unwrap().paused
+    }
+
+    // With a reserve descriptor held, gives it up to accept and close the
+    // connection at the head of the backlog, so the listener does not stay
+    // ready while nothing can be accepted.
+    fn shed(&self, err: io::Error) -> io::Error {
+        if sys::is_fd_exhaustion(&err) {
+            let mut state = self.accept_state.lock().unwrap();
+
+            if state.reserve.take().is_some() {
+                let _ = self.sys.accept();
+                state.reserve = sys::reserve_fd().ok();
+            }
+        }
+
+        err
     }
 
     /// Returns the local socket address of this listener.
@@ -602,6 +1497,7 @@ impl TcpListener {
             TcpListener {
                 sys: s,
                 selector_id: self.selector_id.clone(),
+                accept_state: Mutex::new(AcceptState::default()),
             }
         })
     }
@@ -623,6 +1519,121 @@ impl TcpListener {
         self.sys.ttl()
     }
 
+    /// Sets the value of the `IP_TOS` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_tos`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.set_tos
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        self.sys.set_tos(tos)
+    }
+
+    /// Gets the value of the `IP_TOS` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_tos`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.set_tos
+    pub fn tos(&self) -> io::Result<u32> {
+        self.sys.tos()
+    }
+
+    /// Sets the value of the `IPV6_TCLASS` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_tclass_v6`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.set_tclass_v6
+    pub fn set_tclass_v6(&self, tclass: u32) -> io::Result<()> {
+        self.sys.set_tclass_v6(tclass)
+    }
+
+    /// Gets the value of the `IPV6_TCLASS` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_tclass_v6`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.set_tclass_v6
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        self.sys.tclass_v6()
+    }
+
+    /// Sets the value of the `SO_MARK` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_mark`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.set_mark
+    pub fn set_mark(&self, mark: u32) -> io::Result<()> {
+        self.sys.set_mark(mark)
+    }
+
+    /// Gets the value of the `SO_MARK` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_mark`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.set_mark
+    pub fn mark(&self) -> io::Result<u32> {
+        self.sys.mark()
+    }
+
+    /// Sets the value of the `SO_BINDTODEVICE` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::bind_device`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.bind_device
+    pub fn bind_device(&self, interface: Option<&[u8]>) -> io::Result<()> {
+        self.sys.bind_device(interface)
+    }
+
+    /// Sets the value of the `IP_FREEBIND` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_freebind`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.set_freebind
+    pub fn set_freebind(&self, freebind: bool) -> io::Result<()> {
+        self.sys.set_freebind(freebind)
+    }
+
+    /// Gets the value of the `IP_FREEBIND` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_freebind`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.set_freebind
+    pub fn freebind(&self) -> io::Result<bool> {
+        self.sys.freebind()
+    }
+
+    /// Sets the value of the `IP_TRANSPARENT` option on this socket, or
+    /// `IPV6_TRANSPARENT` for IPv6.
+    ///
+    /// This lets the listener accept connections that an iptables `TPROXY`
+    /// rule steers to it, even though their destination address is not
+    /// local. The original destination is then the [`local_addr`] of each
+    /// accepted stream. Setting it requires the `CAP_NET_ADMIN` capability.
+    ///
+    /// Only supported on Linux.
+    ///
+    /// [`local_addr`]: struct.TcpStream.html#method.local_addr
+    pub fn set_transparent(&self, transparent: bool) -> io::Result<()> {
+        self.sys.set_transparent(transparent)
+    }
+
+    /// Gets the value of the `IP_TRANSPARENT` option on this socket, or
+    /// `IPV6_TRANSPARENT` for IPv6.
+    ///
+    /// For more information about this option, see [`set_transparent`][link].
+    ///
+    /// [link]: #method.set_transparent
+    pub fn transparent(&self) -> io::Result<bool> {
+        self.sys.transparent()
+    }
+
     /// Sets the value for the `IPV6_V6ONLY` option on this socket.
     ///
     /// If this is set to `true` then the socket is restricted to sending and
@@ -644,6 +1655,26 @@ impl TcpListener {
         self.sys.only_v6()
     }
 
+    /// Sets the value of the `TCP_FASTOPEN` option on this socket.
+    ///
+    /// A non-zero `queue_len` enables TCP Fast Open, allowing clients that
+    /// hold a Fast Open cookie to send data in the SYN. `queue_len` bounds the
+    /// number of such connections that have not completed the handshake yet.
+    ///
+    /// Only supported on Linux.
+    pub fn set_fastopen(&self, queue_len: u32) -> io::Result<()> {
+        self.sys.set_fastopen(queue_len)
+    }
+
+    /// Gets the value of the `TCP_FASTOPEN` option on this socket.
+    ///
+    /// For more information about this option, see [`set_fastopen`][link].
+    ///
+    /// [link]: #method.set_fastopen
+    pub fn fastopen(&self) -> io::Result<u32> {
+        self.sys.fastopen()
+    }
+
     /// Get the value of the `SO_ERROR` option on this socket.
     ///
     /// This will retrieve the stored error in the underlying socket, clearing
@@ -658,15 +1689,25 @@ impl Evented for TcpListener {
     fn register(&self, poll: &Poll, token: Token,
                 interest: Ready, opts: PollOpt) -> io::Result<()> {
         self.selector_id.associate_selector(poll)?;
-        self.sys.register(poll, token, interest, opts)
+
+        let mut state = self.accept_state.lock().unwrap();
+        let actual = if state.paused { Ready::empty() } else { interest };
+        self.sys.register(poll, token, actual, opts)?;
+        state.registration = Some((token, interest, opts));
+        Ok(())
     }
 
     fn reregister(&self, poll: &Poll, token: Token,
                   interest: Ready, opts: PollOpt) -> io::Result<()> {
-        self.sys.reregister(poll, token, interest, opts)
+        let mut state = self.accept_state.lock().unwrap();
+        let actual = if state.paused { Ready::empty() } else { interest };
+        self.sys.reregister(poll, token, actual, opts)?;
+        state.registration = Some((token, interest, opts));
+        Ok(())
     }
 
     fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        self.accept_state.lock().unwrap().registration = None;
         self.sys.deregister(poll)
     }
 }
@@ -677,6 +1718,566 @@ impl fmt::Debug for TcpListener {
     }
 }
 
+/*
+ *
+ * ===== TcpSocket =====
+ *
+ */
+
+/// A TCP socket that has not yet been converted to a `TcpStream` or
+/// `TcpListener`.
+///
+/// `TcpSocket` allows configuring options that must be set before the socket
+/// is bound, connected or listening.
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// # fn try_main() -> Result<(), Box<Error>> {
+/// use mio::net::TcpSocket;
+///
+/// let addr = "127.0.0.1:0".parse()?;
+///
+/// let socket = TcpSocket::new_v4()?;
+/// socket.set_reuseaddr(true)?;
+/// socket.set_recv_buffer_size(64 * 1024)?;
+/// socket.bind(&addr)?;
+///
+/// let listener = socket.listen(128)?;
+/// #     drop(listener);
+/// #     Ok(())
+/// # }
+/// #
+/// # fn main() {
+/// #     try_main().unwrap();
+/// # }
+/// ```
+pub struct TcpSocket {
+    builder: TcpBuilder,
+}
+
+impl TcpSocket {
+    /// Creates a new IPv4 TCP socket.
+    pub fn new_v4() -> io::Result<TcpSocket> {
+        TcpBuilder::new_v4().map(|builder| TcpSocket { builder: builder })
+    }
+
+    /// Creates a new IPv6 TCP socket.
+    pub fn new_v6() -> io::Result<TcpSocket> {
+        TcpBuilder::new_v6().map(|builder| TcpSocket { builder: builder })
+    }
+
+    /// Creates a new TCP socket of the same address family as `addr`.
+    pub fn new_for_addr(addr: &SocketAddr) -> io::Result<TcpSocket> {
+        match *addr {
+            SocketAddr::V4(..) => TcpSocket::new_v4(),
+            SocketAddr::V6(..) => TcpSocket::new_v6(),
+        }
+    }
+
+    /// Binds the socket to the given local address.
+    ///
+    /// Binding before calling [`connect`] chooses the source address of the
+    /// connection.
+    ///
+    /// [`connect`]: #method.connect
+    pub fn bind(&self, addr: &SocketAddr) -> io::Result<()> {
+        self.builder.bind(addr).map(|_| ())
+    }
+
+    /// Issues a non-blocking connect to the given address, converting the
+    /// socket into a `TcpStream`.
+    ///
+    /// See [`TcpStream::connect_stream`] for platform specific behavior. On
+    /// Windows, a socket that has not been bound is bound to the unspecified
+    /// address first.
+    ///
+    /// [`TcpStream::connect_stream`]: struct.TcpStream.html#method.connect_stream
+    pub fn connect(self, addr: &SocketAddr) -> io::Result<TcpStream> {
+        // Required on Windows for a future `connect_overlapped` operation to be
+        // executed successfully.
+        if cfg!(windows) && self.builder.local_addr().is_err() {
+            self.builder.bind(&inaddr_any(addr))?;
+        }
+
+        TcpStream::connect_stream(self.builder.to_tcp_stream()?, addr)
+    }
+
+    /// Issues a non-blocking connect to the given address, sending `data`
+    /// with the connection request using TCP Fast Open.
+    ///
+    /// For more information, see [`TcpStream::connect_with_data`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.connect_with_data
+    pub fn connect_with_data(self, addr: &SocketAddr, data: &[u8]) -> io::Result<(TcpStream, usize)> {
+        if cfg!(windows) && self.builder.local_addr().is_err() {
+            self.builder.bind(&inaddr_any(addr))?;
+        }
+
+        let (sys, n) = sys::TcpStream::connect_with_data(self.builder.to_tcp_stream()?, addr, data)?;
+
+        Ok((TcpStream {
+            sys: sys,
+            selector_id: SelectorId::new(),
+        }, n))
+    }
+
+    /// Starts listening for connections, converting the socket into a
+    /// `TcpListener`.
+    ///
+    /// `backlog` is the maximum number of pending connections. The operating
+    /// system may silently cap it.
+    pub fn listen(self, backlog: u32) -> io::Result<TcpListener> {
+        let backlog = cmp::min(backlog, i32::max_value() as u32) as i32;
+        TcpListener::from_std(self.builder.listen(backlog)?)
+    }
+
+    /// Returns the local address the socket is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.builder.local_addr()
+    }
+
+    /// Sets the value of the `SO_REUSEADDR` option on this socket.
+    ///
+    /// This allows binding an address that is still in use by connections in
+    /// the `TIME_WAIT` state.
+    pub fn set_reuseaddr(&self, reuseaddr: bool) -> io::Result<()> {
+        self.builder.reuse_address(reuseaddr).map(|_| ())
+    }
+
+    /// Gets the value of the `SO_REUSEADDR` option on this socket.
+    ///
+    /// For more information about this option, see [`set_reuseaddr`][link].
+    ///
+    /// [link]: #method.set_reuseaddr
+    pub fn reuseaddr(&self) -> io::Result<bool> {
+        self.builder.get_reuse_address()
+    }
+
+    /// Sets the value of the `SO_REUSEPORT` option on this socket.
+    ///
+    /// This allows several sockets to bind the same address, as long as all
+    /// of them set this option. Only available on Unix.
+    #[cfg(all(unix, not(target_os = "solaris")))]
+    pub fn set_reuseport(&self, reuseport: bool) -> io::Result<()> {
+        self.builder.reuse_port(reuseport).map(|_| ())
+    }
+
+    /// Gets the value of the `SO_REUSEPORT` option on this socket.
+    ///
+    /// For more information about this option, see [`set_reuseport`][link].
+    ///
+    /// [link]: #method.set_reuseport
+    #[cfg(all(unix, not(target_os = "solaris")))]
+    pub fn reuseport(&self) -> io::Result<bool> {
+        self.builder.get_reuse_port()
+    }
+
+    /// Sets the value for the `IPV6_V6ONLY` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_only_v6`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.set_only_v6
+    pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        self.builder.only_v6(only_v6).map(|_| ())
+    }
+
+    /// Sets the value of the `SO_RCVBUF` option on this socket.
+    ///
+    /// Setting the receive buffer before connecting or listening allows the
+    /// operating system to pick a matching TCP window scale.
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        with_std_stream(&self.builder, |s| s.set_recv_buffer_size(size))
+    }
+
+    /// Gets the value of the `SO_RCVBUF` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_recv_buffer_size`][link].
+    ///
+    /// [link]: #method.set_recv_buffer_size
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        with_std_stream(&self.builder, |s| s.recv_buffer_size())
+    }
+
+    /// Sets the value of the `SO_SNDBUF` option on this socket.
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        with_std_stream(&self.builder, |s| s.set_send_buffer_size(size))
+    }
+
+    /// Gets the value of the `SO_SNDBUF` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_send_buffer_size`][link].
+    ///
+    /// [link]: #method.set_send_buffer_size
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        with_std_stream(&self.builder, |s| s.send_buffer_size())
+    }
+}
+
+// `TcpBuilder` does not expose the buffer size options, so they are set
+// through a `std::net::TcpStream` borrowing the builder's socket.
+#[cfg(unix)]
+fn with_std_stream<F, T>(builder: &TcpBuilder, f: F) -> io::Result<T>
+    where F: FnOnce(&net::TcpStream) -> io::Result<T>
+{
+    use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+
+    let stream = unsafe { net::TcpStream::from_raw_fd(builder.as_raw_fd()) };
+    let ret = f(&stream);
+    let _ = stream.into_raw_fd();
+    ret
+}
+
+#[cfg(windows)]
+fn with_std_stream<F, T>(builder: &TcpBuilder, f: F) -> io::Result<T>
+    where F: FnOnce(&net::TcpStream) -> io::Result<T>
+{
+    use std::os::windows::io::{AsRawSocket, FromRawSocket, IntoRawSocket};
+
+    let stream = unsafe { net::TcpStream::from_raw_socket(builder.as_raw_socket()) };
+    let ret = f(&stream);
+    let _ = stream.into_raw_socket();
+    ret
+}
+
+impl fmt::Debug for TcpSocket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.builder, f)
+    }
+}
+
+/*
+ *
+ * ===== TcpConnector =====
+ *
+ */
+
+/// Races connections to a list of addresses, in the style of "Happy
+/// Eyeballs" (RFC 8305).
+///
+/// Connection attempts are started one at a time, alternating between IPv6
+/// and IPv4 addresses. If an attempt has not completed within the attempt
+/// delay, the next one is started without canceling the previous ones. An
+/// attempt that fails starts the next one right away. The first attempt to
+/// connect wins and all others are closed.
+///
+/// All attempts are registered with the token given when registering the
+/// connector, and the delay is tracked with [`Poll::register_deadline`]. On
+/// any event for that token, call [`connect`] to drive the attempts.
+///
+/// [`Poll::register_deadline`]: ../struct.Poll.html#method.register_deadline
+/// [`connect`]: #method.connect
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// # fn try_main() -> Result<(), Box<Error>> {
+/// use mio::{Events, Ready, Poll, PollOpt, Token};
+/// use mio::net::{TcpConnector, TcpListener};
+/// use std::io;
+///
+/// let listener = TcpListener::bind(&"127.0.0.1:0".parse()?)?;
+/// let port = listener.local_addr()?.port();
+///
+/// let addrs = vec![
+///     format!("[::1]:{}", port).parse()?,
+///     format!("127.0.0.1:{}", port).parse()?,
+/// ];
+///
+/// let mut connector = TcpConnector::new(&addrs)?;
+///
+/// let poll = Poll::new()?;
+/// let mut events = Events::with_capacity(128);
+///
+/// poll.register(&connector, Token(0), Ready::writable(), PollOpt::edge())?;
+///
+/// let stream;
+///
+/// loop {
+///     poll.poll(&mut events, None)?;
+///
+///     match connector.connect(&poll) {
+///         Ok(s) => {
+///             stream = s;
+///             break;
+///         }
+///         Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+///         Err(e) => return Err(e.into()),
+///     }
+/// }
+///
+/// assert_eq!(port, stream.peer_addr()?.port());
+/// #     Ok(())
+/// # }
+/// #
+/// # fn main() {
+/// #     try_main().unwrap();
+/// # }
+/// ```
+pub struct TcpConnector {
+    // Addresses that have not been attempted yet, in attempt order
+    addrs: VecDeque<SocketAddr>,
+    // Connection attempts in progress
+    attempts: Vec<TcpStream>,
+    // Time to wait for an attempt before starting the next one
+    attempt_delay: Duration,
+    // When the most recent attempt was started
+    last_attempt: Option<Instant>,
+    // The error of the most recent attempt that failed
+    last_error: Option<io::Error>,
+    // Set while registered with a `Poll`
+    registration: Cell<Option<(Token, PollOpt)>>,
+}
+
+impl TcpConnector {
+    /// Creates a new connector for the given addresses and starts the first
+    /// connection attempt.
+    ///
+    /// The attempt delay defaults to 250 milliseconds.
+    pub fn new(addrs: &[SocketAddr]) -> io::Result<TcpConnector> {
+        if addrs.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "no addresses to connect to"));
+        }
+
+        let mut connector = TcpConnector {
+            addrs: interleave(addrs),
+            attempts: Vec::new(),
+            attempt_delay: Duration::from_millis(250),
+            last_attempt: None,
+            last_error: None,
+            registration: Cell::new(None),
+        };
+
+        connector.start_attempt(None)?;
+
+        Ok(connector)
+    }
+
+    /// Sets how long to wait for an attempt to complete before starting the
+    /// next one.
+    pub fn set_attempt_delay(&mut self, delay: Duration) {
+        self.attempt_delay = delay;
+    }
+
+    /// Returns the attempt delay.
+    ///
+    /// For more information, see [`set_attempt_delay`][link].
+    ///
+    /// [link]: #method.set_attempt_delay
+    pub fn attempt_delay(&self) -> Duration {
+        self.attempt_delay
+    }
+
+    /// Drives the connection attempts.
+    ///
+    /// Returns the first stream to connect. The stream stays registered with
+    /// `poll` using the connector's token and options, and writable interest;
+    /// use `Poll::reregister` to change that. All other attempts are closed,
+    /// and the connector is deregistered.
+    ///
+    /// Returns an error with kind `WouldBlock` while attempts are still in
+    /// progress, and the error of the last attempt once all addresses have
+    /// failed.
+    pub fn connect(&mut self, poll: &Poll) -> io::Result<TcpStream> {
+        let mut failed = false;
+        let mut i = 0;
+
+        while i < self.attempts.len() {
+            let result = match self.attempts[i].take_error() {
+                Ok(Some(e)) | Err(e) => Err(e),
+                Ok(None) => self.attempts[i].peer_addr(),
+            };
+
+            match result {
+                Ok(..) => {
+                    let stream = self.attempts.swap_remove(i);
+                    self.finish(poll);
+                    return Ok(stream);
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::NotConnected => {
+                    // Still connecting
+                    i += 1;
+                }
+                Err(e) => {
+                    let attempt = self.attempts.swap_remove(i);
+
+                    if self.registration.get().is_some() {
+                        let _ = poll.deregister(&attempt);
+                    }
+
+                    self.last_error = Some(e);
+                    failed = true;
+                }
+            }
+        }
+
+        let now = Instant::now();
+        let delay_elapsed = match self.last_attempt {
+            Some(last_attempt) => now >= last_attempt + self.attempt_delay,
+            None => true,
+        };
+
+        // A failed attempt starts the next one without waiting for the delay
+        if failed || self.attempts.is_empty() || delay_elapsed {
+            self.start_attempt(Some(poll))?;
+        }
+
+        if self.attempts.is_empty() {
+            self.finish(poll);
+
+            return Err(self.last_error.take().unwrap_or_else(|| {
+                io::Error::new(io::ErrorKind::Other, "all connection attempts failed")
+            }));
+        }
+
+        Err(io::Error::new(io::ErrorKind::WouldBlock, "connection attempts in progress"))
+    }
+
+    // Starts connecting to the next address. Addresses that fail right away
+    // are skipped.
+    fn start_attempt(&mut self, poll: Option<&Poll>) -> io::Result<()> {
+        while let Some(addr) = self.addrs.pop_front() {
+            let stream = match TcpStream::connect(&addr) {
+                Ok(stream) => stream,
+                Err(e) => {
+                    self.last_error = Some(e);
+                    continue;
+                }
+            };
+
+            if let (Some(poll), Some((token, opts))) = (poll, self.registration.get()) {
+                poll.register(&stream, token, Ready::writable(), opts)?;
+            }
+
+            self.attempts.push(stream);
+            self.last_attempt = Some(Instant::now());
+
+            if let Some(poll) = poll {
+                self.schedule_attempt(poll)?;
+            }
+
+            return Ok(());
+        }
+
+        Ok(())
+    }
+
+    // Registers a deadline for starting the next attempt, if there is one
+    fn schedule_attempt(&self, poll: &Poll) -> io::Result<()> {
+        let token = match self.registration.get() {
+            Some((token, _)) => token,
+            None => return Ok(()),
+        };
+
+        if self.attempts.is_empty() {
+            // Every address failed right away, report it on the next poll
+            return poll.register_deadline(token, Instant::now());
+        }
+
+        match self.last_attempt {
+            Some(last_attempt) if !self.addrs.is_empty() => {
+                poll.register_deadline(token, last_attempt + self.attempt_delay)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn finish(&mut self, poll: &Poll) {
+        if self.registration.get().is_some() {
+            let _ = self.deregister(poll);
+        }
+
+        self.attempts.clear();
+        self.addrs.clear();
+    }
+}
+
+// Orders addresses so that address families alternate, starting with the
+// family of the first address.
+fn interleave(addrs: &[SocketAddr]) -> VecDeque<SocketAddr> {
+    let first_v6 = addrs[0].is_ipv6();
+
+    let (mut preferred, mut other): (VecDeque<_>, VecDeque<_>) = addrs.iter()
+        .cloned()
+        .partition(|addr| addr.is_ipv6() == first_v6);
+
+    let mut ret = VecDeque::with_capacity(addrs.len());
+
+    loop {
+        match (preferred.pop_front(), other.pop_front()) {
+            (None, None) => return ret,
+            (a, b) => {
+                ret.extend(a);
+                ret.extend(b);
+            }
+        }
+    }
+}
+
+impl Evented for TcpConnector {
+    fn register(&self, poll: &Poll, token: Token,
+                _: Ready, opts: PollOpt) -> io::Result<()> {
+        if self.registration.get().is_some() {
+            return Err(io::Error::new(io::ErrorKind::Other, "connector already registered"));
+        }
+
+        for attempt in &self.attempts {
+            poll.register(attempt, token, Ready::writable(), opts)?;
+        }
+
+        self.registration.set(Some((token, opts)));
+        self.schedule_attempt(poll)
+    }
+
+    fn reregister(&self, poll: &Poll, token: Token,
+                  _: Ready, opts: PollOpt) -> io::Result<()> {
+        let (prev_token, _) = match self.registration.get() {
+            Some(registration) => registration,
+            None => return Err(io::Error::new(io::ErrorKind::Other, "connector not registered")),
+        };
+
+        for attempt in &self.attempts {
+            poll.reregister(attempt, token, Ready::writable(), opts)?;
+        }
+
+        poll.deregister_deadline(prev_token);
+        self.registration.set(Some((token, opts)));
+        self.schedule_attempt(poll)
+    }
+
+    fn deregister(&self, poll: &Poll) -> io::Result<()> {
+        let (token, _) = match self.registration.get() {
+            Some(registration) => registration,
+            None => return Err(io::Error::new(io::ErrorKind::Other, "connector not registered")),
+        };
+
+        for attempt in &self.attempts {
+            poll.deregister(attempt)?;
+        }
+
+        poll.deregister_deadline(token);
+        self.registration.set(None);
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for TcpConnector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TcpConnector")
+            .field("addrs", &self.addrs)
+            .field("attempts", &self.attempts)
+            .field("attempt_delay", &self.attempt_delay)
+            .finish()
+    }
+}
+
 /*
  *
  * ===== UNIX ext =====
@@ -730,6 +2331,7 @@ impl FromRawFd for TcpListener {
         TcpListener {
             sys: FromRawFd::from_raw_fd(fd),
             selector_id: SelectorId::new(),
+            accept_state: Mutex::new(AcceptState::default()),
         }
     }
 }
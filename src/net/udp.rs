@@ -13,6 +13,10 @@ use poll::SelectorId;
 use std::fmt;
 use std::net::{self, Ipv4Addr, Ipv6Addr, SocketAddr};
 
+use net2::{UdpBuilder, UdpSocketExt};
+#[cfg(all(unix, not(target_os = "solaris")))]
+use net2::unix::UnixUdpBuilderExt;
+
 /// A User Datagram Protocol socket.
 ///
 /// This is an implementation of a bound UDP socket. This supports both IPv4 and
@@ -133,7 +137,10 @@ impl UdpSocket {
     ///
     /// This can be used in conjunction with net2's `UdpBuilder` interface to
     /// configure a socket before it's handed off to mio, such as setting
-    /// options like `reuse_address` or binding to multiple addresses.
+    /// options like `reuse_address` or binding to multiple addresses. The
+    /// common options are also available through [`UdpSocketBuilder`].
+    ///
+    /// [`UdpSocketBuilder`]: struct.UdpSocketBuilder.html
     pub fn from_socket(socket: net::UdpSocket) -> io::Result<UdpSocket> {
         Ok(UdpSocket {
             sys: sys::UdpSocket::new(socket)?,
@@ -268,6 +275,51 @@ impl UdpSocket {
         self.sys.recv_from(buf)
     }
 
+    /// Sets the value of the `IP_RECVORIGDSTADDR` option on this socket, or
+    /// `IPV6_RECVORIGDSTADDR` for IPv6.
+    ///
+    /// If set, [`recv_from_with_dst`] reports the original destination
+    /// address of each datagram, such as one steered to this socket by an
+    /// iptables `TPROXY` rule.
+    ///
+    /// Only supported on Linux.
+    ///
+    /// [`recv_from_with_dst`]: #method.recv_from_with_dst
+    pub fn set_recv_orig_dst_addr(&self, on: bool) -> io::Result<()> {
+        self.sys.set_recv_orig_dst_addr(on)
+    }
+
+    /// Gets the value of the `IP_RECVORIGDSTADDR` option on this socket, or
+    /// `IPV6_RECVORIGDSTADDR` for IPv6.
+    ///
+    /// For more information about this option, see
+    /// [`set_recv_orig_dst_addr`][link].
+    ///
+    /// [link]: #method.set_recv_orig_dst_addr
+    pub fn recv_orig_dst_addr(&self) -> io::Result<bool> {
+        self.sys.recv_orig_dst_addr()
+    }
+
+    /// Receives data from the socket, returning the number of bytes read,
+    /// the source address and the original destination address.
+    ///
+    /// This is [`recv_from`] that also reports where the datagram was
+    /// originally sent. The destination is only known if
+    /// [`set_recv_orig_dst_addr`] was enabled before the datagram arrived,
+    /// and is `None` otherwise.
+    ///
+    /// If other ancillary data enabled on the socket leaves no room for the
+    /// destination, the datagram is consumed and an error is returned.
+    ///
+    /// Only supported on Linux.
+    ///
+    /// [`recv_from`]: #method.recv_from
+    /// [`set_recv_orig_dst_addr`]: #method.set_recv_orig_dst_addr
+    pub fn recv_from_with_dst(&self, buf: &mut [u8])
+                              -> io::Result<(usize, SocketAddr, Option<SocketAddr>)> {
+        self.sys.recv_from_with_dst(buf)
+    }
+
     /// Sends data on the socket to the address previously bound via connect(). On success,
     /// returns the number of bytes written.
     pub fn send(&self, buf: &[u8]) -> io::Result<usize> {
@@ -465,6 +517,96 @@ impl UdpSocket {
         self.sys.ttl()
     }
 
+    /// Sets the value of the `IP_TOS` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_tos`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.set_tos
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        self.sys.set_tos(tos)
+    }
+
+    /// Gets the value of the `IP_TOS` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_tos`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.set_tos
+    pub fn tos(&self) -> io::Result<u32> {
+        self.sys.tos()
+    }
+
+    /// Sets the value of the `IPV6_TCLASS` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_tclass_v6`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.set_tclass_v6
+    pub fn set_tclass_v6(&self, tclass: u32) -> io::Result<()> {
+        self.sys.set_tclass_v6(tclass)
+    }
+
+    /// Gets the value of the `IPV6_TCLASS` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_tclass_v6`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.set_tclass_v6
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        self.sys.tclass_v6()
+    }
+
+    /// Sets the value of the `SO_MARK` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_mark`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.set_mark
+    pub fn set_mark(&self, mark: u32) -> io::Result<()> {
+        self.sys.set_mark(mark)
+    }
+
+    /// Gets the value of the `SO_MARK` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_mark`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.set_mark
+    pub fn mark(&self) -> io::Result<u32> {
+        self.sys.mark()
+    }
+
+    /// Sets the value of the `SO_BINDTODEVICE` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::bind_device`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.bind_device
+    pub fn bind_device(&self, interface: Option<&[u8]>) -> io::Result<()> {
+        self.sys.bind_device(interface)
+    }
+
+    /// Sets the value of the `IP_FREEBIND` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_freebind`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.set_freebind
+    pub fn set_freebind(&self, freebind: bool) -> io::Result<()> {
+        self.sys.set_freebind(freebind)
+    }
+
+    /// Gets the value of the `IP_FREEBIND` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`TcpStream::set_freebind`][link].
+    ///
+    /// [link]: struct.TcpStream.html#method.set_freebind
+    pub fn freebind(&self) -> io::Result<bool> {
+        self.sys.freebind()
+    }
+
     /// Executes an operation of the `IP_ADD_MEMBERSHIP` type.
     ///
     /// This function specifies a new multicast group for this socket to join.
@@ -565,6 +707,169 @@ impl fmt::Debug for UdpSocket {
     }
 }
 
+/*
+ *
+ * ===== UdpSocketBuilder =====
+ *
+ */
+
+/// Configures a UDP socket before it is bound.
+///
+/// # Examples
+///
+/// ```
+/// # use std::error::Error;
+/// # fn try_main() -> Result<(), Box<Error>> {
+/// use mio::net::UdpSocketBuilder;
+///
+/// let builder = UdpSocketBuilder::new_v4()?;
+/// builder.set_reuseaddr(true)?;
+/// builder.set_recv_buffer_size(256 * 1024)?;
+///
+/// let socket = builder.bind(&"127.0.0.1:0".parse()?)?;
+/// #     drop(socket);
+/// #     Ok(())
+/// # }
+/// #
+/// # fn main() {
+/// #     try_main().unwrap();
+/// # }
+/// ```
+pub struct UdpSocketBuilder {
+    builder: UdpBuilder,
+}
+
+impl UdpSocketBuilder {
+    /// Creates a new IPv4 UDP socket.
+    pub fn new_v4() -> io::Result<UdpSocketBuilder> {
+        UdpBuilder::new_v4().map(|builder| UdpSocketBuilder { builder: builder })
+    }
+
+    /// Creates a new IPv6 UDP socket.
+    pub fn new_v6() -> io::Result<UdpSocketBuilder> {
+        UdpBuilder::new_v6().map(|builder| UdpSocketBuilder { builder: builder })
+    }
+
+    /// Creates a new UDP socket of the same address family as `addr`.
+    pub fn new_for_addr(addr: &SocketAddr) -> io::Result<UdpSocketBuilder> {
+        match *addr {
+            SocketAddr::V4(..) => UdpSocketBuilder::new_v4(),
+            SocketAddr::V6(..) => UdpSocketBuilder::new_v6(),
+        }
+    }
+
+    /// Binds the socket to the given address, converting it into a
+    /// `UdpSocket`.
+    pub fn bind(self, addr: &SocketAddr) -> io::Result<UdpSocket> {
+        UdpSocket::from_socket(self.builder.bind(addr)?)
+    }
+
+    /// Sets the value of the `SO_REUSEADDR` option on this socket.
+    pub fn set_reuseaddr(&self, reuseaddr: bool) -> io::Result<()> {
+        self.builder.reuse_address(reuseaddr).map(|_| ())
+    }
+
+    /// Gets the value of the `SO_REUSEADDR` option on this socket.
+    ///
+    /// For more information about this option, see [`set_reuseaddr`][link].
+    ///
+    /// [link]: #method.set_reuseaddr
+    pub fn reuseaddr(&self) -> io::Result<bool> {
+        self.builder.get_reuse_address()
+    }
+
+    /// Sets the value of the `SO_REUSEPORT` option on this socket.
+    ///
+    /// This allows several sockets to bind the same address, as long as all
+    /// of them set this option. Only available on Unix.
+    #[cfg(all(unix, not(target_os = "solaris")))]
+    pub fn set_reuseport(&self, reuseport: bool) -> io::Result<()> {
+        self.builder.reuse_port(reuseport).map(|_| ())
+    }
+
+    /// Gets the value of the `SO_REUSEPORT` option on this socket.
+    ///
+    /// For more information about this option, see [`set_reuseport`][link].
+    ///
+    /// [link]: #method.set_reuseport
+    #[cfg(all(unix, not(target_os = "solaris")))]
+    pub fn reuseport(&self) -> io::Result<bool> {
+        self.builder.get_reuse_port()
+    }
+
+    /// Sets the value for the `IPV6_V6ONLY` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`UdpSocket::set_only_v6`][link].
+    ///
+    /// [link]: struct.UdpSocket.html#method.set_only_v6
+    pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+        self.builder.only_v6(only_v6).map(|_| ())
+    }
+
+    /// Sets the value of the `SO_RCVBUF` option on this socket.
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        with_std_socket(&self.builder, |s| s.set_recv_buffer_size(size))
+    }
+
+    /// Gets the value of the `SO_RCVBUF` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_recv_buffer_size`][link].
+    ///
+    /// [link]: #method.set_recv_buffer_size
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        with_std_socket(&self.builder, |s| s.recv_buffer_size())
+    }
+
+    /// Sets the value of the `SO_SNDBUF` option on this socket.
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        with_std_socket(&self.builder, |s| s.set_send_buffer_size(size))
+    }
+
+    /// Gets the value of the `SO_SNDBUF` option on this socket.
+    ///
+    /// For more information about this option, see
+    /// [`set_send_buffer_size`][link].
+    ///
+    /// [link]: #method.set_send_buffer_size
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        with_std_socket(&self.builder, |s| s.send_buffer_size())
+    }
+}
+
+// `UdpBuilder` does not expose the buffer size options, so they are set
+// through a `std::net::UdpSocket` borrowing the builder's socket.
+#[cfg(unix)]
+fn with_std_socket<F, T>(builder: &UdpBuilder, f: F) -> io::Result<T>
+    where F: FnOnce(&net::UdpSocket) -> io::Result<T>
+{
+    use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd};
+
+    let socket = unsafe { net::UdpSocket::from_raw_fd(builder.as_raw_fd()) };
+    let ret = f(&socket);
+    let _ = socket.into_raw_fd();
+    ret
+}
+
+#[cfg(windows)]
+fn with_std_socket<F, T>(builder: &UdpBuilder, f: F) -> io::Result<T>
+    where F: FnOnce(&net::UdpSocket) -> io::Result<T>
+{
+    use std::os::windows::io::{AsRawSocket, FromRawSocket, IntoRawSocket};
+
+    let socket = unsafe { net::UdpSocket::from_raw_socket(builder.as_raw_socket()) };
+    let ret = f(&socket);
+    let _ = socket.into_raw_socket();
+    ret
+}
+
+impl fmt::Debug for UdpSocketBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.builder, f)
+    }
+}
+
 /*
  *
  * ===== UNIX ext =====
@@ -2,6 +2,7 @@ use {sys, Token};
 use event_imp::{self as event, Ready, Event, Evented, PollOpt};
 use std::{fmt, io, ptr, usize};
 use std::cell::UnsafeCell;
+use std::collections::{BTreeSet, HashMap};
 use std::{mem, ops, isize};
 #[cfg(all(unix, not(target_os = "fuchsia")))]
 use std::os::unix::io::AsRawFd;
@@ -340,6 +341,16 @@ pub struct Poll {
 
     // Wakeup the next waiter
     condvar: Condvar,
+
+    // Deadlines registered with `register_deadline`
+    deadlines: Mutex<Deadlines>,
+}
+
+// Pending deadlines, at most one per token. `queue` orders them by instant so
+// that `poll` can find the earliest one.
+struct Deadlines {
+    queue: BTreeSet<(Instant, Token)>,
+    tokens: HashMap<Token, Instant>,
 }
 
 /// Handle to a user space `Poll` registration.
@@ -568,22 +579,23 @@ struct AtomicState {
 
 const MASK_2: usize = 4 - 1;
 const MASK_4: usize = 16 - 1;
+const MASK_7: usize = 128 - 1;
 const QUEUED_MASK: usize = 1 << QUEUED_SHIFT;
 const DROPPED_MASK: usize = 1 << DROPPED_SHIFT;
 
 const READINESS_SHIFT: usize = 0;
-const INTEREST_SHIFT: usize = 4;
-const POLL_OPT_SHIFT: usize = 8;
-const TOKEN_RD_SHIFT: usize = 12;
-const TOKEN_WR_SHIFT: usize = 14;
-const QUEUED_SHIFT: usize = 16;
-const DROPPED_SHIFT: usize = 17;
+const INTEREST_SHIFT: usize = 7;
+const POLL_OPT_SHIFT: usize = 14;
+const TOKEN_RD_SHIFT: usize = 18;
+const TOKEN_WR_SHIFT: usize = 20;
+const QUEUED_SHIFT: usize = 22;
+const DROPPED_SHIFT: usize = 23;
 
 /// Tracks all state for a single `ReadinessNode`. The state is packed into a
 /// `usize` variable from low to high bit as follows:
 ///
-/// 4 bits: Registration current readiness
-/// 4 bits: Registration interest
+/// 7 bits: Registration current readiness
+/// 7 bits: Registration interest
 /// 4 bits: Poll options
 /// 2 bits: Token position currently being read from by `poll`
 /// 2 bits: Token position last written to by `update`
@@ -657,6 +669,10 @@ impl Poll {
             lock_state: AtomicUsize::new(0),
             lock: Mutex::new(()),
             condvar: Condvar::new(),
+            deadlines: Mutex::new(Deadlines {
+                queue: BTreeSet::new(),
+                tokens: HashMap::new(),
+            }),
         };
 
         // Register the notification wakeup FD with the IO poller
@@ -910,6 +926,95 @@ impl Poll {
         Ok(())
     }
 
+    /// Register a deadline with the `Poll` instance.
+    ///
+    /// Once `deadline` has elapsed, the next call to [`poll`] returns a
+    /// [`readable`] event for `token`. The deadline fires once and is then
+    /// removed. While a deadline is pending, `poll` will not block past it,
+    /// regardless of the `timeout` passed to `poll`.
+    ///
+    /// There is at most one deadline per token: registering a deadline for a
+    /// token that already has one replaces it. Deadlines are independent of
+    /// `Evented` registrations, so the same token may be used for both, in
+    /// which case the handler must be prepared for spurious readable events.
+    ///
+    /// This function may be called from any thread. If the new deadline is
+    /// earlier than the one a concurrent call to `poll` is sleeping until,
+    /// that call is woken up.
+    ///
+    /// [`poll`]: #method.poll
+    /// [`readable`]: struct.Ready.html#method.readable
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::error::Error;
+    /// # fn try_main() -> Result<(), Box<Error>> {
+    /// use mio::{Events, Poll, Token};
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let poll = Poll::new()?;
+    /// let mut events = Events::with_capacity(1024);
+    ///
+    /// poll.register_deadline(Token(0), Instant::now() + Duration::from_millis(50))?;
+    ///
+    /// // `poll` returns once the deadline elapses, even without a timeout
+    /// poll.poll(&mut events, None)?;
+    ///
+    /// let event = events.iter().next().unwrap();
+    /// assert_eq!(event.token(), Token(0));
+    /// assert!(event.readiness().is_readable());
+    /// #     Ok(())
+    /// # }
+    /// #
+    /// # fn main() {
+    /// #     try_main().unwrap();
+    /// # }
+    /// ```
+    pub fn register_deadline(&self, token: Token, deadline: Instant) -> io::Result<()> {
+        validate_args(token)?;
+
+        trace!("registering deadline; token={:?}", token);
+
+        let earliest = {
+            let mut deadlines = self.deadlines.lock().unwrap();
+
+            if let Some(prev) = deadlines.tokens.insert(token, deadline) {
+                deadlines.queue.remove(&(prev, token));
+            }
+
+            deadlines.queue.insert((deadline, token));
+            deadlines.queue.iter().next() == Some(&(deadline, token))
+        };
+
+        // If a thread is currently in `poll`, it may be sleeping past the new
+        // deadline. Otherwise, the next call to `poll` will account for it.
+        if earliest && self.lock_state.load(SeqCst) & 1 == 1 {
+            self.readiness_queue.inner.wakeup()?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove a deadline previously registered with [`register_deadline`].
+    ///
+    /// Returns `true` if a deadline was pending for `token`.
+    ///
+    /// [`register_deadline`]: #method.register_deadline
+    pub fn deregister_deadline(&self, token: Token) -> bool {
+        trace!("deregistering deadline; token={:?}", token);
+
+        let mut deadlines = self.deadlines.lock().unwrap();
+
+        match deadlines.tokens.remove(&token) {
+            Some(deadline) => {
+                deadlines.queue.remove(&(deadline, token));
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Wait for readiness events
     ///
     /// Blocks the current thread and waits for readiness events for any of the
@@ -1150,6 +1255,21 @@ impl Poll {
 
     #[inline]
     fn poll2(&self, events: &mut Events, mut timeout: Option<Duration>, interruptible: bool) -> io::Result<usize> {
+        // Do not block past the earliest pending deadline
+        if let Some(&(deadline, _)) = self.deadlines.lock().unwrap().queue.iter().next() {
+            let now = Instant::now();
+            let until = if deadline > now {
+                deadline - now
+            } else {
+                Duration::from_millis(0)
+            };
+
+            timeout = match timeout {
+                Some(to) if to < until => Some(to),
+                _ => Some(until),
+            };
+        }
+
         // Compute the timeout value passed to the system selector. If the
         // readiness queue has pending nodes, we still want to poll the system
         // selector for new events, but we don't want to block the thread to
@@ -1198,9 +1318,37 @@ impl Poll {
         // Poll custom event queue
         self.readiness_queue.poll(&mut events.inner);
 
+        // Fire elapsed deadlines
+        self.poll_deadlines(&mut events.inner);
+
         // Return number of polled events
         Ok(events.inner.len())
     }
+
+    fn poll_deadlines(&self, dst: &mut sys::Events) {
+        let mut deadlines = self.deadlines.lock().unwrap();
+
+        if deadlines.queue.is_empty() {
+            return;
+        }
+
+        let now = Instant::now();
+
+        // Deadlines that do not fit in `dst` are returned by the next `poll`
+        while dst.len() < dst.capacity() {
+            let (deadline, token) = match deadlines.queue.iter().next() {
+                Some(&(deadline, token)) if deadline <= now => (deadline, token),
+                _ => break,
+            };
+
+            trace!("deadline elapsed; token={:?}", token);
+
+            deadlines.queue.remove(&(deadline, token));
+            deadlines.tokens.remove(&token);
+
+            dst.push_event(Event::new(Ready::readable(), token));
+        }
+    }
 }
 
 fn validate_args(token: Token) -> io::Result<()> {
@@ -2579,7 +2727,7 @@ impl ReadinessState {
         let interest = event::ready_as_usize(interest);
         let opt = event::opt_as_usize(opt);
 
-        debug_assert!(interest <= MASK_4);
+        debug_assert!(interest <= MASK_7);
         debug_assert!(opt <= MASK_4);
 
         let mut val = interest << INTEREST_SHIFT;
@@ -2601,7 +2749,7 @@ impl ReadinessState {
     /// Get the readiness
     #[inline]
     fn readiness(&self) -> Ready {
-        let v = self.get(MASK_4, READINESS_SHIFT);
+        let v = self.get(MASK_7, READINESS_SHIFT);
         event::ready_from_usize(v)
     }
 
@@ -2613,20 +2761,20 @@ impl ReadinessState {
     /// Set the readiness
     #[inline]
     fn set_readiness(&mut self, v: Ready) {
-        self.set(event::ready_as_usize(v), MASK_4, READINESS_SHIFT);
+        self.set(event::ready_as_usize(v), MASK_7, READINESS_SHIFT);
     }
 
     /// Get the interest
     #[inline]
     fn interest(&self) -> Ready {
-        let v = self.get(MASK_4, INTEREST_SHIFT);
+        let v = self.get(MASK_7, INTEREST_SHIFT);
         event::ready_from_usize(v)
     }
 
     /// Set the interest
     #[inline]
     fn set_interest(&mut self, v: Ready) {
-        self.set(event::ready_as_usize(v), MASK_4, INTEREST_SHIFT);
+        self.set(event::ready_as_usize(v), MASK_7, INTEREST_SHIFT);
     }
 
     #[inline]
@@ -1,5 +1,6 @@
-use {io, Evented, Ready, Poll, PollOpt, Token};
+use {io, sys, Evented, Ready, Poll, PollOpt, Token};
 use iovec::IoVec;
+use net::{RecvFlags, SendFlags};
 use iovec::unix as iovec;
 use libc;
 use net2::TcpStreamExt;
@@ -7,6 +8,7 @@ use net2::TcpStreamExt;
 use net2::UdpSocketExt;
 use sys::fuchsia::{recv_from, set_nonblock, EventedFd, DontDrop};
 use std::cmp;
+use std::fs::File;
 use std::io::{Read, Write};
 use std::net::{self, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::os::unix::io::AsRawFd;
@@ -37,6 +39,11 @@ impl TcpStream {
         })
     }
 
+    pub fn connect_with_data(stream: net::TcpStream, addr: &SocketAddr, _: &[u8])
+                             -> io::Result<(TcpStream, usize)> {
+        TcpStream::connect(stream, addr).map(|s| (s, 0))
+    }
+
     pub fn from_stream(stream: net::TcpStream) -> TcpStream {
         let evented_fd = unsafe { EventedFd::new(stream.as_raw_fd()) };
 
@@ -76,6 +83,58 @@ impl TcpStream {
         self.io.nodelay()
     }
 
+    pub fn recv_with_flags(&self, _: &mut [u8], _: RecvFlags) -> io::Result<usize> {
+        Err(sys::unsupported())
+    }
+
+    pub fn send_with_flags(&self, _: &[u8], _: SendFlags) -> io::Result<usize> {
+        Err(sys::unsupported())
+    }
+
+    pub fn recv_bufs_with_flags(&self, _: &mut [&mut IoVec], _: RecvFlags) -> io::Result<usize> {
+        Err(sys::unsupported())
+    }
+
+    pub fn send_bufs_with_flags(&self, _: &[&IoVec], _: SendFlags) -> io::Result<usize> {
+        Err(sys::unsupported())
+    }
+
+    pub fn send_oob(&self, _: u8) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn recv_oob(&self) -> io::Result<u8> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_oobinline(&self, _: bool) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn oobinline(&self) -> io::Result<bool> {
+        Err(sys::unsupported())
+    }
+
+    pub fn original_dst(&self) -> io::Result<SocketAddr> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_notsent_lowat(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn notsent_lowat(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_cork(&self, _: bool) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn cork(&self) -> io::Result<bool> {
+        Err(sys::unsupported())
+    }
+
     pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
         self.io.set_recv_buffer_size(size)
     }
@@ -100,6 +159,30 @@ impl TcpStream {
         self.io.keepalive()
     }
 
+    pub fn set_keepalive_interval(&self, _: Duration) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn keepalive_interval(&self) -> io::Result<Duration> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_keepalive_retries(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn keepalive_retries(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_user_timeout(&self, _: Option<Duration>) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn user_timeout(&self) -> io::Result<Option<Duration>> {
+        Err(sys::unsupported())
+    }
+
     pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
         self.io.set_ttl(ttl)
     }
@@ -108,6 +191,42 @@ impl TcpStream {
         self.io.ttl()
     }
 
+    pub fn set_tos(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn tos(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_tclass_v6(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_mark(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn mark(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn bind_device(&self, _: Option<&[u8]>) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_freebind(&self, _: bool) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn freebind(&self) -> io::Result<bool> {
+        Err(sys::unsupported())
+    }
+
     pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
         self.io.set_only_v6(only_v6)
     }
@@ -132,6 +251,26 @@ impl TcpStream {
         self.io.peek(buf)
     }
 
+    pub fn send_file(&self, _: &File, _: u64, _: usize) -> io::Result<usize> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_zerocopy(&self, _: bool) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn zerocopy(&self) -> io::Result<bool> {
+        Err(sys::unsupported())
+    }
+
+    pub fn send_zerocopy(&self, _: &[u8]) -> io::Result<usize> {
+        Err(sys::unsupported())
+    }
+
+    pub fn zerocopy_completion(&self) -> io::Result<(u32, u32, bool)> {
+        Err(sys::unsupported())
+    }
+
     pub fn readv(&self, bufs: &mut [&mut IoVec]) -> io::Result<usize> {
         unsafe {
             let slice = iovec::as_os_slice_mut(bufs);
@@ -234,6 +373,10 @@ impl TcpListener {
         })
     }
 
+    pub fn accept_nonblocking(&self) -> io::Result<(TcpStream, SocketAddr)> {
+        self.accept()
+    }
+
     pub fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
         self.io.accept().and_then(|(s, a)| {
             set_nonblock(s.as_raw_fd())?;
@@ -263,6 +406,62 @@ impl TcpListener {
         self.io.ttl()
     }
 
+    pub fn set_tos(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn tos(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_tclass_v6(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_mark(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn mark(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn bind_device(&self, _: Option<&[u8]>) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_freebind(&self, _: bool) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn freebind(&self) -> io::Result<bool> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_transparent(&self, _: bool) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn transparent(&self) -> io::Result<bool> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_cpu_steering(&self, _: usize) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_fastopen(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn fastopen(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
     pub fn take_error(&self) -> io::Result<Option<io::Error>> {
         self.io.take_error()
     }
@@ -324,6 +523,19 @@ impl UdpSocket {
         self.io.send_to(buf, target)
     }
 
+    pub fn set_recv_orig_dst_addr(&self, _: bool) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn recv_orig_dst_addr(&self) -> io::Result<bool> {
+        Err(sys::unsupported())
+    }
+
+    pub fn recv_from_with_dst(&self, _: &mut [u8])
+                              -> io::Result<(usize, SocketAddr, Option<SocketAddr>)> {
+        Err(sys::unsupported())
+    }
+
     pub fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
         unsafe { recv_from(self.io.as_raw_fd(), buf) }
     }
@@ -377,6 +589,42 @@ impl UdpSocket {
         self.io.ttl()
     }
 
+    pub fn set_tos(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn tos(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_tclass_v6(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_mark(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn mark(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn bind_device(&self, _: Option<&[u8]>) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_freebind(&self, _: bool) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn freebind(&self) -> io::Result<bool> {
+        Err(sys::unsupported())
+    }
+
     pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
         self.io.set_ttl(ttl)
     }
@@ -54,3 +54,36 @@ pub mod fuchsia;
 
 #[cfg(not(all(unix, not(target_os = "fuchsia"))))]
 pub const READY_ALL: usize = 0;
+
+// Error for socket options and operations that the target platform does not
+// provide.
+pub fn unsupported() -> ::io::Error {
+    ::io::Error::new(::io::ErrorKind::Other, "operation not supported on this platform")
+}
+
+// Whether `err` means the process or the system ran out of descriptors.
+#[cfg(unix)]
+pub fn is_fd_exhaustion(err: &::io::Error) -> bool {
+    match err.raw_os_error() {
+        Some(::libc::EMFILE) | Some(::libc::ENFILE) => true,
+        _ => false,
+    }
+}
+
+#[cfg(windows)]
+pub fn is_fd_exhaustion(err: &::io::Error) -> bool {
+    // WSAEMFILE
+    err.raw_os_error() == Some(10024)
+}
+
+// Opens a descriptor that is held in reserve and given up when the process
+// runs out.
+#[cfg(unix)]
+pub fn reserve_fd() -> ::io::Result<::std::fs::File> {
+    ::std::fs::File::open("/dev/null")
+}
+
+#[cfg(windows)]
+pub fn reserve_fd() -> ::io::Result<::std::fs::File> {
+    ::std::fs::File::open("NUL")
+}
@@ -22,7 +22,10 @@ pub use self::kqueue::{Events, Selector};
 mod awakener;
 mod eventedfd;
 mod io;
+mod net;
 mod ready;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod splice;
 mod tcp;
 mod udp;
 
@@ -33,6 +36,8 @@ pub use self::awakener::Awakener;
 pub use self::eventedfd::EventedFd;
 pub use self::io::{Io, set_nonblock};
 pub use self::ready::{UnixReady, READY_ALL};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub use self::splice::SplicePipe;
 pub use self::tcp::{TcpStream, TcpListener};
 pub use self::udp::UdpSocket;
 
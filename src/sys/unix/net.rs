@@ -0,0 +1,183 @@
+//! Socket option and address helpers for the options `std` and `net2` do not
+//! expose.
+
+use std::mem;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use std::os::unix::io::RawFd;
+
+use libc::{self, c_int, c_void, socklen_t};
+
+use io;
+use sys::unix::cvt;
+
+pub fn setsockopt<T: Copy>(fd: RawFd, level: c_int, name: c_int, val: T) -> io::Result<()> {
+    unsafe {
+        let payload = &val as *const T as *const c_void;
+        cvt(libc::setsockopt(fd, level, name, payload,
+                             mem::size_of::<T>() as socklen_t))?;
+    }
+    Ok(())
+}
+
+pub fn getsockopt<T: Copy>(fd: RawFd, level: c_int, name: c_int) -> io::Result<T> {
+    unsafe {
+        let mut slot: T = mem::zeroed();
+        let mut len = mem::size_of::<T>() as socklen_t;
+        cvt(libc::getsockopt(fd, level, name,
+                             &mut slot as *mut T as *mut c_void,
+                             &mut len))?;
+        Ok(slot)
+    }
+}
+
+/// Converts `addr` to a C socket address, returning it along with its
+/// length.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn socket_addr(addr: &SocketAddr) -> (libc::sockaddr_storage, socklen_t) {
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+
+    let len = match *addr {
+        SocketAddr::V4(ref addr) => {
+            let sin = unsafe { &mut *(&mut storage as *mut _ as *mut libc::sockaddr_in) };
+            sin.sin_family = libc::AF_INET as libc::sa_family_t;
+            sin.sin_port = addr.port().to_be();
+            sin.sin_addr.s_addr = u32::from(*addr.ip()).to_be();
+            mem::size_of::<libc::sockaddr_in>()
+        }
+        SocketAddr::V6(ref addr) => {
+            let sin6 = unsafe { &mut *(&mut storage as *mut _ as *mut libc::sockaddr_in6) };
+            sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+            sin6.sin6_port = addr.port().to_be();
+            sin6.sin6_flowinfo = addr.flowinfo();
+            sin6.sin6_addr.s6_addr = addr.ip().octets();
+            sin6.sin6_scope_id = addr.scope_id();
+            mem::size_of::<libc::sockaddr_in6>()
+        }
+    };
+
+    (storage, len as socklen_t)
+}
+
+/// Converts a C socket address filled in by the kernel back to a
+/// `SocketAddr`.
+pub fn to_socket_addr(storage: &libc::sockaddr_storage, len: socklen_t) -> io::Result<SocketAddr> {
+    match storage.ss_family as c_int {
+        libc::AF_INET if len as usize >= mem::size_of::<libc::sockaddr_in>() => {
+            let sin = unsafe { &*(storage as *const _ as *const libc::sockaddr_in) };
+            let ip = Ipv4Addr::from(u32::from_be(sin.sin_addr.s_addr));
+            Ok(SocketAddr::V4(SocketAddrV4::new(ip, u16::from_be(sin.sin_port))))
+        }
+        libc::AF_INET6 if len as usize >= mem::size_of::<libc::sockaddr_in6>() => {
+            let sin6 = unsafe { &*(storage as *const _ as *const libc::sockaddr_in6) };
+            let ip = Ipv6Addr::from(sin6.sin6_addr.s6_addr);
+            Ok(SocketAddr::V6(SocketAddrV6::new(ip, u16::from_be(sin6.sin6_port),
+                                                sin6.sin6_flowinfo, sin6.sin6_scope_id)))
+        }
+        _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid socket address")),
+    }
+}
+
+// Not available in all supported versions of `libc`. The values are the
+// ones from `asm-generic/socket.h` and `linux/in.h`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SO_MARK: c_int = 36;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SO_BINDTODEVICE: c_int = 25;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const IP_FREEBIND: c_int = 15;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn set_tos(fd: RawFd, tos: u32) -> io::Result<()> {
+    setsockopt(fd, libc::IPPROTO_IP, libc::IP_TOS, tos as c_int)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn set_tos(_: RawFd, _: u32) -> io::Result<()> {
+    Err(::sys::unsupported())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn tos(fd: RawFd) -> io::Result<u32> {
+    getsockopt::<c_int>(fd, libc::IPPROTO_IP, libc::IP_TOS).map(|tos| tos as u32)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn tos(_: RawFd) -> io::Result<u32> {
+    Err(::sys::unsupported())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn set_tclass_v6(fd: RawFd, tclass: u32) -> io::Result<()> {
+    setsockopt(fd, libc::IPPROTO_IPV6, libc::IPV6_TCLASS, tclass as c_int)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn set_tclass_v6(_: RawFd, _: u32) -> io::Result<()> {
+    Err(::sys::unsupported())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn tclass_v6(fd: RawFd) -> io::Result<u32> {
+    getsockopt::<c_int>(fd, libc::IPPROTO_IPV6, libc::IPV6_TCLASS).map(|tclass| tclass as u32)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn tclass_v6(_: RawFd) -> io::Result<u32> {
+    Err(::sys::unsupported())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn set_mark(fd: RawFd, mark: u32) -> io::Result<()> {
+    setsockopt(fd, libc::SOL_SOCKET, SO_MARK, mark as c_int)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn set_mark(_: RawFd, _: u32) -> io::Result<()> {
+    Err(::sys::unsupported())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn mark(fd: RawFd) -> io::Result<u32> {
+    getsockopt::<c_int>(fd, libc::SOL_SOCKET, SO_MARK).map(|mark| mark as u32)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn mark(_: RawFd) -> io::Result<u32> {
+    Err(::sys::unsupported())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn bind_device(fd: RawFd, interface: Option<&[u8]>) -> io::Result<()> {
+    let interface = interface.unwrap_or(&[]);
+    unsafe {
+        cvt(libc::setsockopt(fd, libc::SOL_SOCKET, SO_BINDTODEVICE,
+                             interface.as_ptr() as *const c_void,
+                             interface.len() as socklen_t))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn bind_device(_: RawFd, _: Option<&[u8]>) -> io::Result<()> {
+    Err(::sys::unsupported())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn set_freebind(fd: RawFd, freebind: bool) -> io::Result<()> {
+    setsockopt(fd, libc::IPPROTO_IP, IP_FREEBIND, freebind as c_int)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn set_freebind(_: RawFd, _: bool) -> io::Result<()> {
+    Err(::sys::unsupported())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn freebind(fd: RawFd) -> io::Result<bool> {
+    getsockopt::<c_int>(fd, libc::IPPROTO_IP, IP_FREEBIND).map(|freebind| freebind != 0)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn freebind(_: RawFd) -> io::Result<bool> {
+    Err(::sys::unsupported())
+}
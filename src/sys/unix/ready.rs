@@ -110,8 +110,9 @@ const LIO: usize   = 0b100000;
 const LIO: usize   = 0b000000;
 
 
+// A bit of its own, as the value of `EPOLLPRI` clashes with `WRITABLE`
 #[cfg(any(target_os = "linux", target_os = "android", target_os = "solaris"))]
-const PRI: usize = ::libc::EPOLLPRI as usize;
+const PRI: usize = 0b1000000;
 
 
 // Export to support `Ready::all`
@@ -0,0 +1,133 @@
+use std::fmt;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::ptr;
+
+use libc;
+
+use io;
+use sys::unix::{cvt, pipe, Io};
+
+/// A pipe for moving data between two file descriptors with `splice(2)`,
+/// without copying it through user space.
+///
+/// Data is spliced from a source, such as a socket, into the pipe with
+/// [`splice_from`], and from the pipe into a destination with
+/// [`splice_to`]. Both are non-blocking: an error with kind `WouldBlock` is
+/// returned if the source has no data, the destination has no space, or the
+/// pipe itself is full, so they can be driven from an edge-triggered event
+/// loop like any other read and write.
+///
+/// [`splice_from`]: #method.splice_from
+/// [`splice_to`]: #method.splice_to
+///
+/// # Examples
+///
+/// Proxying data from one stream to another:
+///
+/// ```no_run
+/// # use std::error::Error;
+/// # fn try_main() -> Result<(), Box<Error>> {
+/// use mio::net::TcpStream;
+/// use mio::unix::SplicePipe;
+/// use std::io;
+///
+/// # let client = TcpStream::connect(&"127.0.0.1:8000".parse()?)?;
+/// # let upstream = TcpStream::connect(&"127.0.0.1:8001".parse()?)?;
+/// let mut pipe = SplicePipe::new()?;
+///
+/// // Called on readable events for `client` and writable events for
+/// // `upstream`
+/// loop {
+///     if pipe.is_empty() {
+///         match pipe.splice_from(&client, 64 * 1024) {
+///             Ok(0) => break, // EOF
+///             Ok(_) => {}
+///             Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+///             Err(e) => return Err(e.into()),
+///         }
+///     }
+///
+///     match pipe.splice_to(&upstream, 64 * 1024) {
+///         Ok(_) => {}
+///         Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+///         Err(e) => return Err(e.into()),
+///     }
+/// }
+/// #     Ok(())
+/// # }
+/// #
+/// # fn main() {
+/// #     try_main().unwrap();
+/// # }
+/// ```
+pub struct SplicePipe {
+    reader: Io,
+    writer: Io,
+    // Number of bytes currently held in the pipe
+    len: usize,
+}
+
+impl SplicePipe {
+    /// Creates a new, empty pipe.
+    pub fn new() -> io::Result<SplicePipe> {
+        let (reader, writer) = pipe()?;
+
+        Ok(SplicePipe {
+            reader: reader,
+            writer: writer,
+            len: 0,
+        })
+    }
+
+    /// Moves up to `max` bytes from `src` into the pipe.
+    ///
+    /// Returns the number of bytes moved, `0` meaning that `src` reached end
+    /// of file.
+    pub fn splice_from<S: AsRawFd>(&mut self, src: &S, max: usize) -> io::Result<usize> {
+        let n = splice(src.as_raw_fd(), self.writer.as_raw_fd(), max)?;
+        self.len += n;
+        Ok(n)
+    }
+
+    /// Moves up to `max` bytes from the pipe into `dst`.
+    ///
+    /// Returns the number of bytes moved, which is `0` if the pipe is empty.
+    pub fn splice_to<S: AsRawFd>(&mut self, dst: &S, max: usize) -> io::Result<usize> {
+        if self.len == 0 {
+            return Ok(0);
+        }
+
+        let n = splice(self.reader.as_raw_fd(), dst.as_raw_fd(), max)?;
+        self.len -= n;
+        Ok(n)
+    }
+
+    /// Returns the number of bytes held in the pipe.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the pipe holds no data.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+fn splice(src: RawFd, dst: RawFd, max: usize) -> io::Result<usize> {
+    let flags = libc::SPLICE_F_MOVE | libc::SPLICE_F_NONBLOCK;
+
+    unsafe {
+        cvt(libc::splice(src, ptr::null_mut(), dst, ptr::null_mut(), max, flags))
+            .map(|n| n as usize)
+    }
+}
+
+impl fmt::Debug for SplicePipe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SplicePipe")
+            .field("reader", &self.reader)
+            .field("writer", &self.writer)
+            .field("len", &self.len)
+            .finish()
+    }
+}
@@ -1,5 +1,6 @@
 use std::cmp;
 use std::fmt;
+use std::fs::File;
 use std::io::{Read, Write};
 use std::net::{self, SocketAddr};
 use std::os::unix::io::{RawFd, FromRawFd, IntoRawFd, AsRawFd};
@@ -11,15 +12,193 @@ use iovec::IoVec;
 use iovec::unix as iovec;
 
 use {io, Ready, Poll, PollOpt, Token};
+use net::{RecvFlags, SendFlags};
 use event::Evented;
 
 use sys::unix::eventedfd::EventedFd;
 use sys::unix::io::set_nonblock;
+use sys::unix::net::{getsockopt, setsockopt, to_socket_addr};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use sys::unix::net::socket_addr;
 
 pub struct TcpStream {
     inner: net::TcpStream,
 }
 
+// Not available in all supported versions of `libc`
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SO_ZEROCOPY: libc::c_int = 60;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const MSG_ZEROCOPY: libc::c_int = 0x4000000;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SO_EE_ORIGIN_ZEROCOPY: u8 = 5;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SO_EE_CODE_ZEROCOPY_COPIED: u8 = 1;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SO_ATTACH_REUSEPORT_CBPF: libc::c_int = 51;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const IP_TRANSPARENT: libc::c_int = 19;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const IPV6_TRANSPARENT: libc::c_int = 75;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SO_ORIGINAL_DST: libc::c_int = 80;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const IP6T_SO_ORIGINAL_DST: libc::c_int = 80;
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+unsafe fn accept_nonblocking(fd: RawFd, addr: *mut libc::sockaddr, len: *mut libc::socklen_t)
+                             -> io::Result<RawFd> {
+    let flags = libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC;
+    match libc::accept4(fd, addr, len, flags) {
+        -1 => Err(io::Error::last_os_error()),
+        fd => Ok(fd),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+unsafe fn accept_nonblocking(fd: RawFd, addr: *mut libc::sockaddr, len: *mut libc::socklen_t)
+                             -> io::Result<RawFd> {
+    match libc::accept(fd, addr, len) {
+        -1 => Err(io::Error::last_os_error()),
+        fd => {
+            // Close the socket if either call fails
+            let stream = net::TcpStream::from_raw_fd(fd);
+            ::sys::unix::io::set_cloexec(fd)?;
+            set_nonblock(fd)?;
+            Ok(stream.into_raw_fd())
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const TCP_USER_TIMEOUT: libc::c_int = 18;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const TCP_NOTSENT_LOWAT: libc::c_int = 25;
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+const TCP_NOTSENT_LOWAT: libc::c_int = 0x201;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const MSG_TRUNC: Option<libc::c_int> = Some(libc::MSG_TRUNC);
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const MSG_TRUNC: Option<libc::c_int> = None;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const MSG_MORE: Option<libc::c_int> = Some(libc::MSG_MORE);
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const MSG_MORE: Option<libc::c_int> = None;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const MSG_NOSIGNAL: Option<libc::c_int> = Some(libc::MSG_NOSIGNAL);
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const MSG_NOSIGNAL: Option<libc::c_int> = None;
+
+fn recv_flags(flags: RecvFlags) -> io::Result<libc::c_int> {
+    let mut raw = 0;
+
+    if flags.is_peek() {
+        raw |= libc::MSG_PEEK;
+    }
+    if flags.is_wait_all() {
+        raw |= libc::MSG_WAITALL;
+    }
+    if flags.is_trunc() {
+        raw |= MSG_TRUNC.ok_or_else(::sys::unsupported)?;
+    }
+
+    Ok(raw)
+}
+
+fn send_flags(flags: SendFlags) -> io::Result<libc::c_int> {
+    let mut raw = 0;
+
+    if flags.is_more() {
+        raw |= MSG_MORE.ok_or_else(::sys::unsupported)?;
+    }
+    if flags.is_no_signal() {
+        raw |= MSG_NOSIGNAL.ok_or_else(::sys::unsupported)?;
+    }
+    if flags.is_dont_route() {
+        raw |= libc::MSG_DONTROUTE;
+    }
+
+    Ok(raw)
+}
+
+// Rounds up to whole seconds, since zero is rejected
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd",
+          target_os = "macos", target_os = "ios"))]
+fn dur2secs(dur: Duration) -> libc::c_int {
+    let secs = dur.as_secs() + if dur.subsec_nanos() > 0 { 1 } else { 0 };
+    cmp::min(secs, libc::c_int::max_value() as u64) as libc::c_int
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn dur2millis(dur: Duration) -> libc::c_uint {
+    let ms = dur.as_secs()
+        .saturating_mul(1_000)
+        .saturating_add((dur.subsec_nanos() as u64 + 999_999) / 1_000_000);
+    cmp::min(ms, libc::c_uint::max_value() as u64) as libc::c_uint
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn transparent_opt(addr: &SocketAddr) -> (libc::c_int, libc::c_int) {
+    match *addr {
+        SocketAddr::V4(..) => (libc::SOL_IP, IP_TRANSPARENT),
+        SocketAddr::V6(..) => (libc::SOL_IPV6, IPV6_TRANSPARENT),
+    }
+}
+
+// Classic BPF, from `linux/filter.h`
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SockFilter {
+    code: u16,
+    jt: u8,
+    jf: u8,
+    k: u32,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SockFprog {
+    len: u16,
+    filter: *const SockFilter,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const BPF_LD: u16 = 0x00;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const BPF_ALU: u16 = 0x04;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const BPF_RET: u16 = 0x06;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const BPF_W: u16 = 0x00;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const BPF_ABS: u16 = 0x20;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const BPF_MOD: u16 = 0x90;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const BPF_K: u16 = 0x00;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const BPF_A: u16 = 0x10;
+// `SKF_AD_OFF + SKF_AD_CPU`, the CPU handling the packet
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SKF_AD_CPU: u32 = (-0x1000i32 + 36) as u32;
+
+// `struct sock_extended_err` from `linux/errqueue.h`
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct SockExtendedErr {
+    ee_errno: u32,
+    ee_origin: u8,
+    ee_type: u8,
+    ee_code: u8,
+    ee_pad: u8,
+    ee_info: u32,
+    ee_data: u32,
+}
+
 pub struct TcpListener {
     inner: net::TcpListener,
 }
@@ -39,6 +218,44 @@ impl TcpStream {
         })
     }
 
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn connect_with_data(stream: net::TcpStream, addr: &SocketAddr, data: &[u8])
+                             -> io::Result<(TcpStream, usize)> {
+        set_nonblock(stream.as_raw_fd())?;
+
+        let (addr_storage, addr_len) = socket_addr(addr);
+        let rc = unsafe {
+            libc::sendto(stream.as_raw_fd(),
+                         data.as_ptr() as *const libc::c_void,
+                         data.len(),
+                         libc::MSG_FASTOPEN | libc::MSG_NOSIGNAL,
+                         &addr_storage as *const _ as *const libc::sockaddr,
+                         addr_len)
+        };
+
+        let n = if rc < 0 {
+            let err = io::Error::last_os_error();
+
+            match err.raw_os_error() {
+                // No cookie yet, the SYN went out without data
+                Some(libc::EINPROGRESS) => 0,
+                // TFO is disabled for clients, fall back to a plain connect
+                Some(libc::EOPNOTSUPP) => return TcpStream::connect(stream, addr).map(|s| (s, 0)),
+                _ => return Err(err),
+            }
+        } else {
+            rc as usize
+        };
+
+        Ok((TcpStream { inner: stream }, n))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn connect_with_data(stream: net::TcpStream, addr: &SocketAddr, _: &[u8])
+                             -> io::Result<(TcpStream, usize)> {
+        TcpStream::connect(stream, addr).map(|s| (s, 0))
+    }
+
     pub fn from_stream(stream: net::TcpStream) -> TcpStream {
         TcpStream {
             inner: stream,
@@ -73,6 +290,54 @@ impl TcpStream {
         self.inner.nodelay()
     }
 
+    #[cfg(any(target_os = "linux", target_os = "android",
+              target_os = "macos", target_os = "ios"))]
+    pub fn set_notsent_lowat(&self, bytes: u32) -> io::Result<()> {
+        setsockopt(self.inner.as_raw_fd(), libc::IPPROTO_TCP, TCP_NOTSENT_LOWAT,
+                   bytes as libc::c_uint)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android",
+                  target_os = "macos", target_os = "ios")))]
+    pub fn set_notsent_lowat(&self, _: u32) -> io::Result<()> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android",
+              target_os = "macos", target_os = "ios"))]
+    pub fn notsent_lowat(&self) -> io::Result<u32> {
+        getsockopt::<libc::c_uint>(self.inner.as_raw_fd(), libc::IPPROTO_TCP, TCP_NOTSENT_LOWAT)
+            .map(|bytes| bytes as u32)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android",
+                  target_os = "macos", target_os = "ios")))]
+    pub fn notsent_lowat(&self) -> io::Result<u32> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn set_cork(&self, cork: bool) -> io::Result<()> {
+        setsockopt(self.inner.as_raw_fd(), libc::IPPROTO_TCP, libc::TCP_CORK,
+                   cork as libc::c_int)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn set_cork(&self, _: bool) -> io::Result<()> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn cork(&self) -> io::Result<bool> {
+        getsockopt::<libc::c_int>(self.inner.as_raw_fd(), libc::IPPROTO_TCP, libc::TCP_CORK)
+            .map(|cork| cork != 0)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn cork(&self) -> io::Result<bool> {
+        Err(::sys::unsupported())
+    }
+
     pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
         self.inner.set_recv_buffer_size(size)
     }
@@ -97,6 +362,86 @@ impl TcpStream {
         self.inner.keepalive()
     }
 
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd",
+              target_os = "macos", target_os = "ios"))]
+    pub fn set_keepalive_interval(&self, interval: Duration) -> io::Result<()> {
+        setsockopt(self.inner.as_raw_fd(), libc::IPPROTO_TCP, libc::TCP_KEEPINTVL,
+                   dur2secs(interval))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd",
+                  target_os = "macos", target_os = "ios")))]
+    pub fn set_keepalive_interval(&self, _: Duration) -> io::Result<()> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd",
+              target_os = "macos", target_os = "ios"))]
+    pub fn keepalive_interval(&self) -> io::Result<Duration> {
+        getsockopt::<libc::c_int>(self.inner.as_raw_fd(), libc::IPPROTO_TCP, libc::TCP_KEEPINTVL)
+            .map(|secs| Duration::from_secs(secs as u64))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd",
+                  target_os = "macos", target_os = "ios")))]
+    pub fn keepalive_interval(&self) -> io::Result<Duration> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd",
+              target_os = "macos", target_os = "ios"))]
+    pub fn set_keepalive_retries(&self, retries: u32) -> io::Result<()> {
+        setsockopt(self.inner.as_raw_fd(), libc::IPPROTO_TCP, libc::TCP_KEEPCNT,
+                   cmp::min(retries, libc::c_int::max_value() as u32) as libc::c_int)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd",
+                  target_os = "macos", target_os = "ios")))]
+    pub fn set_keepalive_retries(&self, _: u32) -> io::Result<()> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd",
+              target_os = "macos", target_os = "ios"))]
+    pub fn keepalive_retries(&self) -> io::Result<u32> {
+        getsockopt::<libc::c_int>(self.inner.as_raw_fd(), libc::IPPROTO_TCP, libc::TCP_KEEPCNT)
+            .map(|n| n as u32)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd",
+                  target_os = "macos", target_os = "ios")))]
+    pub fn keepalive_retries(&self) -> io::Result<u32> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn set_user_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        let ms = timeout.map(dur2millis).unwrap_or(0);
+        setsockopt(self.inner.as_raw_fd(), libc::IPPROTO_TCP, TCP_USER_TIMEOUT, ms)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn set_user_timeout(&self, _: Option<Duration>) -> io::Result<()> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn user_timeout(&self) -> io::Result<Option<Duration>> {
+        getsockopt::<libc::c_uint>(self.inner.as_raw_fd(), libc::IPPROTO_TCP, TCP_USER_TIMEOUT)
+            .map(|ms| {
+                if ms == 0 {
+                    None
+                } else {
+                    Some(Duration::from_millis(ms as u64))
+                }
+            })
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn user_timeout(&self) -> io::Result<Option<Duration>> {
+        Err(::sys::unsupported())
+    }
+
     pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
         self.inner.set_ttl(ttl)
     }
@@ -105,6 +450,42 @@ impl TcpStream {
         self.inner.ttl()
     }
 
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        ::sys::unix::net::set_tos(self.as_raw_fd(), tos)
+    }
+
+    pub fn tos(&self) -> io::Result<u32> {
+        ::sys::unix::net::tos(self.as_raw_fd())
+    }
+
+    pub fn set_tclass_v6(&self, tclass: u32) -> io::Result<()> {
+        ::sys::unix::net::set_tclass_v6(self.as_raw_fd(), tclass)
+    }
+
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        ::sys::unix::net::tclass_v6(self.as_raw_fd())
+    }
+
+    pub fn set_mark(&self, mark: u32) -> io::Result<()> {
+        ::sys::unix::net::set_mark(self.as_raw_fd(), mark)
+    }
+
+    pub fn mark(&self) -> io::Result<u32> {
+        ::sys::unix::net::mark(self.as_raw_fd())
+    }
+
+    pub fn bind_device(&self, interface: Option<&[u8]>) -> io::Result<()> {
+        ::sys::unix::net::bind_device(self.as_raw_fd(), interface)
+    }
+
+    pub fn set_freebind(&self, freebind: bool) -> io::Result<()> {
+        ::sys::unix::net::set_freebind(self.as_raw_fd(), freebind)
+    }
+
+    pub fn freebind(&self) -> io::Result<bool> {
+        ::sys::unix::net::freebind(self.as_raw_fd())
+    }
+
     pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
         self.inner.set_only_v6(only_v6)
     }
@@ -129,6 +510,172 @@ impl TcpStream {
         self.inner.peek(buf)
     }
 
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn send_file(&self, file: &File, offset: u64, len: usize) -> io::Result<usize> {
+        if offset > libc::off_t::max_value() as u64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "offset out of range"));
+        }
+
+        let mut offset = offset as libc::off_t;
+
+        unsafe {
+            let rc = libc::sendfile(self.inner.as_raw_fd(), file.as_raw_fd(), &mut offset, len);
+            if rc < 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(rc as usize)
+            }
+        }
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn send_file(&self, _: &File, _: u64, _: usize) -> io::Result<usize> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn set_zerocopy(&self, zerocopy: bool) -> io::Result<()> {
+        setsockopt(self.inner.as_raw_fd(), libc::SOL_SOCKET, SO_ZEROCOPY,
+                   zerocopy as libc::c_int)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn set_zerocopy(&self, _: bool) -> io::Result<()> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn zerocopy(&self) -> io::Result<bool> {
+        getsockopt::<libc::c_int>(self.inner.as_raw_fd(), libc::SOL_SOCKET, SO_ZEROCOPY)
+            .map(|n| n != 0)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn zerocopy(&self) -> io::Result<bool> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn send_zerocopy(&self, buf: &[u8]) -> io::Result<usize> {
+        unsafe {
+            let rc = libc::send(self.inner.as_raw_fd(),
+                                buf.as_ptr() as *const libc::c_void,
+                                buf.len(),
+                                MSG_ZEROCOPY | libc::MSG_NOSIGNAL);
+            if rc < 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(rc as usize)
+            }
+        }
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn send_zerocopy(&self, _: &[u8]) -> io::Result<usize> {
+        Err(::sys::unsupported())
+    }
+
+    /// Reads one notification from the socket error queue, returning the
+    /// inclusive range of completed sends and whether the kernel fell back to
+    /// copying.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn zerocopy_completion(&self) -> io::Result<(u32, u32, bool)> {
+        use std::mem;
+        use std::ptr;
+
+        // Large enough for a single `sock_extended_err` and the offending
+        // address, and aligned for `cmsghdr`
+        let mut control = [0u64; 16];
+
+        let err = unsafe {
+            let mut msg: libc::msghdr = mem::zeroed();
+            msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+            msg.msg_controllen = mem::size_of_val(&control) as _;
+
+            if libc::recvmsg(self.inner.as_raw_fd(), &mut msg, libc::MSG_ERRQUEUE) < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            if msg.msg_flags & libc::MSG_CTRUNC != 0 {
+                return Err(io::Error::new(io::ErrorKind::Other, "control messages truncated"));
+            }
+
+            // Each message read from the error queue carries a single
+            // control message
+            if (msg.msg_controllen as usize) < mem::size_of::<libc::cmsghdr>() {
+                return Err(io::Error::new(io::ErrorKind::Other, "missing control message"));
+            }
+
+            let cmsg = &*(control.as_ptr() as *const libc::cmsghdr);
+
+            // The data follows the header, aligned to `size_of::<usize>()`
+            let align = mem::size_of::<usize>();
+            let offset = (mem::size_of::<libc::cmsghdr>() + align - 1) & !(align - 1);
+
+            if (cmsg.cmsg_len as usize) < offset + mem::size_of::<SockExtendedErr>() ||
+                cmsg.cmsg_len as usize > msg.msg_controllen as usize {
+                return Err(io::Error::new(io::ErrorKind::Other, "short control message"));
+            }
+
+            let is_recverr = (cmsg.cmsg_level == libc::SOL_IP && cmsg.cmsg_type == libc::IP_RECVERR) ||
+                (cmsg.cmsg_level == libc::SOL_IPV6 && cmsg.cmsg_type == libc::IPV6_RECVERR);
+
+            if !is_recverr {
+                return Err(io::Error::new(io::ErrorKind::Other, "unexpected control message"));
+            }
+
+            let data = (control.as_ptr() as *const u8).offset(offset as isize);
+
+            ptr::read(data as *const SockExtendedErr)
+        };
+
+        if err.ee_origin != SO_EE_ORIGIN_ZEROCOPY {
+            return Err(io::Error::from_raw_os_error(err.ee_errno as i32));
+        }
+
+        Ok((err.ee_info, err.ee_data, err.ee_code & SO_EE_CODE_ZEROCOPY_COPIED != 0))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn zerocopy_completion(&self) -> io::Result<(u32, u32, bool)> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn original_dst(&self) -> io::Result<SocketAddr> {
+        use std::mem;
+
+        let (level, name) = match self.inner.local_addr()? {
+            SocketAddr::V4(..) => (libc::SOL_IP, SO_ORIGINAL_DST),
+            SocketAddr::V6(..) => (libc::SOL_IPV6, IP6T_SO_ORIGINAL_DST),
+        };
+
+        let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let mut len = mem::size_of_val(&storage) as libc::socklen_t;
+
+        let rc = unsafe {
+            libc::getsockopt(self.inner.as_raw_fd(), level, name,
+                             &mut storage as *mut _ as *mut libc::c_void,
+                             &mut len)
+        };
+        if rc < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        to_socket_addr(&storage, len)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn original_dst(&self) -> io::Result<SocketAddr> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn tcp_info(&self) -> io::Result<TcpInfo> {
+        // Older kernels fill in less, leaving the remaining fields zeroed
+        getsockopt(self.inner.as_raw_fd(), libc::IPPROTO_TCP, libc::TCP_INFO)
+    }
+
     pub fn readv(&self, bufs: &mut [&mut IoVec]) -> io::Result<usize> {
         unsafe {
             let slice = iovec::as_os_slice_mut(bufs);
@@ -144,6 +691,116 @@ impl TcpStream {
         }
     }
 
+    pub fn recv_with_flags(&self, buf: &mut [u8], flags: RecvFlags) -> io::Result<usize> {
+        let flags = recv_flags(flags)?;
+        let rc = unsafe {
+            libc::recv(self.inner.as_raw_fd(),
+                       buf.as_mut_ptr() as *mut libc::c_void,
+                       buf.len(),
+                       flags)
+        };
+        if rc < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(rc as usize)
+        }
+    }
+
+    pub fn send_with_flags(&self, buf: &[u8], flags: SendFlags) -> io::Result<usize> {
+        let flags = send_flags(flags)?;
+        let rc = unsafe {
+            libc::send(self.inner.as_raw_fd(),
+                       buf.as_ptr() as *const libc::c_void,
+                       buf.len(),
+                       flags)
+        };
+        if rc < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(rc as usize)
+        }
+    }
+
+    pub fn recv_bufs_with_flags(&self, bufs: &mut [&mut IoVec], flags: RecvFlags)
+                                -> io::Result<usize> {
+        use std::mem;
+
+        let flags = recv_flags(flags)?;
+        unsafe {
+            let slice = iovec::as_os_slice_mut(bufs);
+            let len = cmp::min(<libc::c_int>::max_value() as usize, slice.len());
+            let mut msg: libc::msghdr = mem::zeroed();
+            msg.msg_iov = slice.as_mut_ptr();
+            msg.msg_iovlen = len as _;
+
+            let rc = libc::recvmsg(self.inner.as_raw_fd(), &mut msg, flags);
+            if rc < 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(rc as usize)
+            }
+        }
+    }
+
+    pub fn send_bufs_with_flags(&self, bufs: &[&IoVec], flags: SendFlags) -> io::Result<usize> {
+        use std::mem;
+
+        let flags = send_flags(flags)?;
+        unsafe {
+            let slice = iovec::as_os_slice(bufs);
+            let len = cmp::min(<libc::c_int>::max_value() as usize, slice.len());
+            let mut msg: libc::msghdr = mem::zeroed();
+            msg.msg_iov = slice.as_ptr() as *mut libc::iovec;
+            msg.msg_iovlen = len as _;
+
+            let rc = libc::sendmsg(self.inner.as_raw_fd(), &msg, flags);
+            if rc < 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(rc as usize)
+            }
+        }
+    }
+
+    pub fn send_oob(&self, byte: u8) -> io::Result<()> {
+        let rc = unsafe {
+            libc::send(self.inner.as_raw_fd(),
+                       &byte as *const u8 as *const libc::c_void,
+                       1,
+                       libc::MSG_OOB)
+        };
+        match rc {
+            -1 => Err(io::Error::last_os_error()),
+            0 => Err(io::ErrorKind::WouldBlock.into()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn recv_oob(&self) -> io::Result<u8> {
+        let mut byte = 0u8;
+        let rc = unsafe {
+            libc::recv(self.inner.as_raw_fd(),
+                       &mut byte as *mut u8 as *mut libc::c_void,
+                       1,
+                       libc::MSG_OOB)
+        };
+        match rc {
+            -1 => Err(io::Error::last_os_error()),
+            0 => Err(io::ErrorKind::UnexpectedEof.into()),
+            _ => Ok(byte),
+        }
+    }
+
+    pub fn set_oobinline(&self, oobinline: bool) -> io::Result<()> {
+        setsockopt(self.inner.as_raw_fd(), libc::SOL_SOCKET, libc::SO_OOBINLINE,
+                   oobinline as libc::c_int)
+    }
+
+    pub fn oobinline(&self) -> io::Result<bool> {
+        getsockopt::<libc::c_int>(self.inner.as_raw_fd(), libc::SOL_SOCKET, libc::SO_OOBINLINE)
+            .map(|oobinline| oobinline != 0)
+    }
+
     pub fn writev(&self, bufs: &[&IoVec]) -> io::Result<usize> {
         unsafe {
             let slice = iovec::as_os_slice(bufs);
@@ -160,6 +817,66 @@ impl TcpStream {
     }
 }
 
+// The leading part of `struct tcp_info` from `linux/tcp.h`, up to
+// `tcpi_delivery_rate`
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct TcpInfo {
+    pub tcpi_state: u8,
+    pub tcpi_ca_state: u8,
+    pub tcpi_retransmits: u8,
+    pub tcpi_probes: u8,
+    pub tcpi_backoff: u8,
+    pub tcpi_options: u8,
+    pub tcpi_wscale: u8,
+    pub tcpi_flags: u8,
+
+    pub tcpi_rto: u32,
+    pub tcpi_ato: u32,
+    pub tcpi_snd_mss: u32,
+    pub tcpi_rcv_mss: u32,
+
+    pub tcpi_unacked: u32,
+    pub tcpi_sacked: u32,
+    pub tcpi_lost: u32,
+    pub tcpi_retrans: u32,
+    pub tcpi_fackets: u32,
+
+    pub tcpi_last_data_sent: u32,
+    pub tcpi_last_ack_sent: u32,
+    pub tcpi_last_data_recv: u32,
+    pub tcpi_last_ack_recv: u32,
+
+    pub tcpi_pmtu: u32,
+    pub tcpi_rcv_ssthresh: u32,
+    pub tcpi_rtt: u32,
+    pub tcpi_rttvar: u32,
+    pub tcpi_snd_ssthresh: u32,
+    pub tcpi_snd_cwnd: u32,
+    pub tcpi_advmss: u32,
+    pub tcpi_reordering: u32,
+
+    pub tcpi_rcv_rtt: u32,
+    pub tcpi_rcv_space: u32,
+
+    pub tcpi_total_retrans: u32,
+
+    pub tcpi_pacing_rate: u64,
+    pub tcpi_max_pacing_rate: u64,
+    pub tcpi_bytes_acked: u64,
+    pub tcpi_bytes_received: u64,
+    pub tcpi_segs_out: u32,
+    pub tcpi_segs_in: u32,
+
+    pub tcpi_notsent_bytes: u32,
+    pub tcpi_min_rtt: u32,
+    pub tcpi_data_segs_in: u32,
+    pub tcpi_data_segs_out: u32,
+
+    pub tcpi_delivery_rate: u64,
+}
+
 impl<'a> Read for &'a TcpStream {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         (&self.inner).read(buf)
@@ -242,6 +959,25 @@ impl TcpListener {
         self.inner.accept()
     }
 
+    /// Accepts a connection that is already nonblocking and close-on-exec,
+    /// using a single `accept4` call where available.
+    pub fn accept_nonblocking(&self) -> io::Result<(TcpStream, SocketAddr)> {
+        use std::mem;
+
+        let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let mut len = mem::size_of_val(&storage) as libc::socklen_t;
+
+        let fd = unsafe {
+            accept_nonblocking(self.inner.as_raw_fd(),
+                               &mut storage as *mut _ as *mut libc::sockaddr,
+                               &mut len)?
+        };
+        let stream = unsafe { net::TcpStream::from_raw_fd(fd) };
+        let addr = to_socket_addr(&storage, len)?;
+
+        Ok((TcpStream::from_stream(stream), addr))
+    }
+
     #[allow(deprecated)]
     pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
         self.inner.set_only_v6(only_v6)
@@ -260,6 +996,113 @@ impl TcpListener {
         self.inner.ttl()
     }
 
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        ::sys::unix::net::set_tos(self.as_raw_fd(), tos)
+    }
+
+    pub fn tos(&self) -> io::Result<u32> {
+        ::sys::unix::net::tos(self.as_raw_fd())
+    }
+
+    pub fn set_tclass_v6(&self, tclass: u32) -> io::Result<()> {
+        ::sys::unix::net::set_tclass_v6(self.as_raw_fd(), tclass)
+    }
+
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        ::sys::unix::net::tclass_v6(self.as_raw_fd())
+    }
+
+    pub fn set_mark(&self, mark: u32) -> io::Result<()> {
+        ::sys::unix::net::set_mark(self.as_raw_fd(), mark)
+    }
+
+    pub fn mark(&self) -> io::Result<u32> {
+        ::sys::unix::net::mark(self.as_raw_fd())
+    }
+
+    pub fn bind_device(&self, interface: Option<&[u8]>) -> io::Result<()> {
+        ::sys::unix::net::bind_device(self.as_raw_fd(), interface)
+    }
+
+    pub fn set_freebind(&self, freebind: bool) -> io::Result<()> {
+        ::sys::unix::net::set_freebind(self.as_raw_fd(), freebind)
+    }
+
+    pub fn freebind(&self) -> io::Result<bool> {
+        ::sys::unix::net::freebind(self.as_raw_fd())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn set_fastopen(&self, queue_len: u32) -> io::Result<()> {
+        setsockopt(self.inner.as_raw_fd(), libc::IPPROTO_TCP, libc::TCP_FASTOPEN,
+                   queue_len as libc::c_int)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn set_fastopen(&self, _: u32) -> io::Result<()> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn fastopen(&self) -> io::Result<u32> {
+        getsockopt::<libc::c_int>(self.inner.as_raw_fd(), libc::IPPROTO_TCP, libc::TCP_FASTOPEN)
+            .map(|n| n as u32)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn fastopen(&self) -> io::Result<u32> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn set_transparent(&self, transparent: bool) -> io::Result<()> {
+        let (level, name) = transparent_opt(&self.inner.local_addr()?);
+        setsockopt(self.inner.as_raw_fd(), level, name, transparent as libc::c_int)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn set_transparent(&self, _: bool) -> io::Result<()> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn transparent(&self) -> io::Result<bool> {
+        let (level, name) = transparent_opt(&self.inner.local_addr()?);
+        getsockopt::<libc::c_int>(self.inner.as_raw_fd(), level, name)
+            .map(|transparent| transparent != 0)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn transparent(&self) -> io::Result<bool> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn set_cpu_steering(&self, group_size: usize) -> io::Result<()> {
+        if group_size == 0 || group_size > u32::max_value() as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                                      "invalid reuseport group size"));
+        }
+
+        // A = current CPU; A %= group_size; return A
+        let program = [
+            SockFilter { code: BPF_LD | BPF_W | BPF_ABS, jt: 0, jf: 0, k: SKF_AD_CPU },
+            SockFilter { code: BPF_ALU | BPF_MOD | BPF_K, jt: 0, jf: 0, k: group_size as u32 },
+            SockFilter { code: BPF_RET | BPF_A, jt: 0, jf: 0, k: 0 },
+        ];
+        let prog = SockFprog {
+            len: program.len() as u16,
+            filter: program.as_ptr(),
+        };
+
+        setsockopt(self.inner.as_raw_fd(), libc::SOL_SOCKET, SO_ATTACH_REUSEPORT_CBPF, prog)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn set_cpu_steering(&self, _: usize) -> io::Result<()> {
+        Err(::sys::unsupported())
+    }
+
     pub fn take_error(&self) -> io::Result<Option<io::Error>> {
         self.inner.take_error()
     }
@@ -5,6 +5,11 @@ use std::fmt;
 use std::net::{self, Ipv4Addr, Ipv6Addr, SocketAddr};
 use std::os::unix::io::{RawFd, IntoRawFd, AsRawFd, FromRawFd};
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use libc;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use sys::unix::net::{getsockopt, setsockopt, to_socket_addr};
+
 #[allow(unused_imports)] // only here for Rust 1.8
 use net2::UdpSocketExt;
 
@@ -40,6 +45,104 @@ impl UdpSocket {
         self.io.recv_from(buf)
     }
 
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn set_recv_orig_dst_addr(&self, on: bool) -> io::Result<()> {
+        let (level, name) = recv_orig_dst_addr_opt(&self.io.local_addr()?);
+        setsockopt(self.as_raw_fd(), level, name, on as libc::c_int)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn set_recv_orig_dst_addr(&self, _: bool) -> io::Result<()> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn recv_orig_dst_addr(&self) -> io::Result<bool> {
+        let (level, name) = recv_orig_dst_addr_opt(&self.io.local_addr()?);
+        getsockopt::<libc::c_int>(self.as_raw_fd(), level, name)
+            .map(|on| on != 0)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn recv_orig_dst_addr(&self) -> io::Result<bool> {
+        Err(::sys::unsupported())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn recv_from_with_dst(&self, buf: &mut [u8])
+                              -> io::Result<(usize, SocketAddr, Option<SocketAddr>)> {
+        use std::{cmp, mem, ptr};
+
+        let mut src: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let mut iov = libc::iovec {
+            iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+            iov_len: buf.len(),
+        };
+        // Room for a few control messages, aligned for `cmsghdr`
+        let mut control = [0u64; 32];
+
+        let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+        msg.msg_name = &mut src as *mut _ as *mut libc::c_void;
+        msg.msg_namelen = mem::size_of_val(&src) as libc::socklen_t;
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+        msg.msg_controllen = mem::size_of_val(&control) as _;
+
+        let n = unsafe { libc::recvmsg(self.as_raw_fd(), &mut msg, 0) };
+        if n < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        if msg.msg_flags & libc::MSG_CTRUNC != 0 {
+            // Other ancillary data filled the buffer, so the destination may
+            // be missing
+            return Err(io::Error::new(io::ErrorKind::Other, "control messages truncated"));
+        }
+
+        let src = to_socket_addr(&src, msg.msg_namelen)?;
+
+        // Walk the control messages, each aligned to `size_of::<usize>()`
+        let align = mem::size_of::<usize>();
+        let header = (mem::size_of::<libc::cmsghdr>() + align - 1) & !(align - 1);
+        let controllen = msg.msg_controllen as usize;
+        let base = control.as_ptr() as *const u8;
+        let mut offset = 0;
+        let mut dst = None;
+
+        while offset + mem::size_of::<libc::cmsghdr>() <= controllen {
+            let cmsg = unsafe { ptr::read(base.offset(offset as isize) as *const libc::cmsghdr) };
+            let len = cmsg.cmsg_len as usize;
+            if len < header || offset + len > controllen {
+                break;
+            }
+
+            let is_orig_dst = (cmsg.cmsg_level == libc::SOL_IP && cmsg.cmsg_type == IP_ORIGDSTADDR) ||
+                (cmsg.cmsg_level == libc::SOL_IPV6 && cmsg.cmsg_type == IPV6_ORIGDSTADDR);
+
+            if is_orig_dst {
+                let mut addr: libc::sockaddr_storage = unsafe { mem::zeroed() };
+                let data_len = cmp::min(len - header, mem::size_of_val(&addr));
+                unsafe {
+                    ptr::copy_nonoverlapping(base.offset((offset + header) as isize),
+                                             &mut addr as *mut _ as *mut u8,
+                                             data_len);
+                }
+                dst = Some(to_socket_addr(&addr, data_len as libc::socklen_t)?);
+            }
+
+            offset += (len + align - 1) & !(align - 1);
+        }
+
+        Ok((n as usize, src, dst))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn recv_from_with_dst(&self, _: &mut [u8])
+                              -> io::Result<(usize, SocketAddr, Option<SocketAddr>)> {
+        Err(::sys::unsupported())
+    }
+
     pub fn send(&self, buf: &[u8]) -> io::Result<usize> {
         self.io.send(buf)
     }
@@ -89,6 +192,42 @@ impl UdpSocket {
         self.io.ttl()
     }
 
+    pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+        ::sys::unix::net::set_tos(self.as_raw_fd(), tos)
+    }
+
+    pub fn tos(&self) -> io::Result<u32> {
+        ::sys::unix::net::tos(self.as_raw_fd())
+    }
+
+    pub fn set_tclass_v6(&self, tclass: u32) -> io::Result<()> {
+        ::sys::unix::net::set_tclass_v6(self.as_raw_fd(), tclass)
+    }
+
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        ::sys::unix::net::tclass_v6(self.as_raw_fd())
+    }
+
+    pub fn set_mark(&self, mark: u32) -> io::Result<()> {
+        ::sys::unix::net::set_mark(self.as_raw_fd(), mark)
+    }
+
+    pub fn mark(&self) -> io::Result<u32> {
+        ::sys::unix::net::mark(self.as_raw_fd())
+    }
+
+    pub fn bind_device(&self, interface: Option<&[u8]>) -> io::Result<()> {
+        ::sys::unix::net::bind_device(self.as_raw_fd(), interface)
+    }
+
+    pub fn set_freebind(&self, freebind: bool) -> io::Result<()> {
+        ::sys::unix::net::set_freebind(self.as_raw_fd(), freebind)
+    }
+
+    pub fn freebind(&self) -> io::Result<bool> {
+        ::sys::unix::net::freebind(self.as_raw_fd())
+    }
+
     pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
         self.io.set_ttl(ttl)
     }
@@ -130,6 +269,21 @@ impl UdpSocket {
     }
 }
 
+// Not available in all supported versions of `libc`. The control message
+// types have the same values as the options that enable them.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const IP_ORIGDSTADDR: libc::c_int = 20;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const IPV6_ORIGDSTADDR: libc::c_int = 74;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn recv_orig_dst_addr_opt(addr: &SocketAddr) -> (libc::c_int, libc::c_int) {
+    match *addr {
+        SocketAddr::V4(..) => (libc::SOL_IP, IP_ORIGDSTADDR),
+        SocketAddr::V6(..) => (libc::SOL_IPV6, IPV6_ORIGDSTADDR),
+    }
+}
+
 impl Evented for UdpSocket {
     fn register(&self, poll: &Poll, token: Token, interest: Ready, opts: PollOpt) -> io::Result<()> {
         EventedFd(&self.as_raw_fd()).register(poll, token, interest, opts)
@@ -1,4 +1,5 @@
 use std::fmt;
+use std::fs::File;
 use std::io::{self, Read, ErrorKind};
 use std::mem;
 use std::net::{self, SocketAddr, Shutdown};
@@ -12,8 +13,9 @@ use net2::{TcpBuilder, TcpStreamExt as Net2TcpExt};
 use winapi::*;
 use iovec::IoVec;
 
-use {poll, Ready, Poll, PollOpt, Token};
+use {poll, sys, Ready, Poll, PollOpt, Token};
 use event::Evented;
+use net::{RecvFlags, SendFlags};
 use sys::windows::from_raw_arc::FromRawArc;
 use sys::windows::selector::{Overlapped, ReadyBinding};
 use sys::windows::Family;
@@ -119,6 +121,11 @@ impl TcpStream {
         Ok(TcpStream::new(socket, Some(*addr)))
     }
 
+    pub fn connect_with_data(socket: net::TcpStream, addr: &SocketAddr, _: &[u8])
+                             -> io::Result<(TcpStream, usize)> {
+        TcpStream::connect(socket, addr).map(|s| (s, 0))
+    }
+
     pub fn from_stream(stream: net::TcpStream) -> TcpStream {
         TcpStream::new(stream, None)
     }
@@ -147,6 +154,58 @@ impl TcpStream {
         self.imp.inner.socket.nodelay()
     }
 
+    pub fn recv_with_flags(&self, _: &mut [u8], _: RecvFlags) -> io::Result<usize> {
+        Err(sys::unsupported())
+    }
+
+    pub fn send_with_flags(&self, _: &[u8], _: SendFlags) -> io::Result<usize> {
+        Err(sys::unsupported())
+    }
+
+    pub fn recv_bufs_with_flags(&self, _: &mut [&mut IoVec], _: RecvFlags) -> io::Result<usize> {
+        Err(sys::unsupported())
+    }
+
+    pub fn send_bufs_with_flags(&self, _: &[&IoVec], _: SendFlags) -> io::Result<usize> {
+        Err(sys::unsupported())
+    }
+
+    pub fn send_oob(&self, _: u8) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn recv_oob(&self) -> io::Result<u8> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_oobinline(&self, _: bool) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn oobinline(&self) -> io::Result<bool> {
+        Err(sys::unsupported())
+    }
+
+    pub fn original_dst(&self) -> io::Result<SocketAddr> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_notsent_lowat(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn notsent_lowat(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_cork(&self, _: bool) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn cork(&self) -> io::Result<bool> {
+        Err(sys::unsupported())
+    }
+
     pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
         self.imp.inner.socket.set_recv_buffer_size(size)
     }
@@ -171,6 +230,30 @@ impl TcpStream {
         self.imp.inner.socket.keepalive()
     }
 
+    pub fn set_keepalive_interval(&self, _: Duration) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn keepalive_interval(&self) -> io::Result<Duration> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_keepalive_retries(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn keepalive_retries(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_user_timeout(&self, _: Option<Duration>) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn user_timeout(&self) -> io::Result<Option<Duration>> {
+        Err(sys::unsupported())
+    }
+
     pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
         self.imp.inner.socket.set_ttl(ttl)
     }
@@ -179,6 +262,42 @@ impl TcpStream {
         self.imp.inner.socket.ttl()
     }
 
+    pub fn set_tos(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn tos(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_tclass_v6(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_mark(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn mark(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn bind_device(&self, _: Option<&[u8]>) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_freebind(&self, _: bool) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn freebind(&self) -> io::Result<bool> {
+        Err(sys::unsupported())
+    }
+
     pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
         self.imp.inner.socket.set_only_v6(only_v6)
     }
@@ -296,6 +415,26 @@ impl TcpStream {
         }
     }
 
+    pub fn send_file(&self, _: &File, _: u64, _: usize) -> io::Result<usize> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_zerocopy(&self, _: bool) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn zerocopy(&self) -> io::Result<bool> {
+        Err(sys::unsupported())
+    }
+
+    pub fn send_zerocopy(&self, _: &[u8]) -> io::Result<usize> {
+        Err(sys::unsupported())
+    }
+
+    pub fn zerocopy_completion(&self) -> io::Result<(u32, u32, bool)> {
+        Err(sys::unsupported())
+    }
+
     pub fn readv(&self, bufs: &mut [&mut IoVec]) -> io::Result<usize> {
         let mut me = self.before_read()?;
 
@@ -673,6 +812,12 @@ impl TcpListener {
         }
     }
 
+    pub fn accept_nonblocking(&self) -> io::Result<(TcpStream, SocketAddr)> {
+        let (s, a) = self.accept()?;
+        s.set_nonblocking(true)?;
+        Ok((TcpStream::from_stream(s), a))
+    }
+
     pub fn accept(&self) -> io::Result<(net::TcpStream, SocketAddr)> {
         let mut me = self.inner();
 
@@ -719,6 +864,62 @@ impl TcpListener {
         self.imp.inner.socket.ttl()
     }
 
+    pub fn set_tos(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn tos(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_tclass_v6(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_mark(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn mark(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn bind_device(&self, _: Option<&[u8]>) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_freebind(&self, _: bool) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn freebind(&self) -> io::Result<bool> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_transparent(&self, _: bool) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn transparent(&self) -> io::Result<bool> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_cpu_steering(&self, _: usize) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_fastopen(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn fastopen(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
     pub fn take_error(&self) -> io::Result<Option<io::Error>> {
         self.imp.inner.socket.take_error()
     }
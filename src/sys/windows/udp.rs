@@ -17,7 +17,7 @@ use miow::iocp::CompletionStatus;
 use miow::net::SocketAddrBuf;
 use miow::net::UdpSocketExt as MiowUdpSocketExt;
 
-use {poll, Ready, Poll, PollOpt, Token};
+use {poll, sys, Ready, Poll, PollOpt, Token};
 use event::Evented;
 use sys::windows::from_raw_arc::FromRawArc;
 use sys::windows::selector::{Overlapped, ReadyBinding};
@@ -150,6 +150,19 @@ impl UdpSocket {
         Ok(amt)
     }
 
+    pub fn set_recv_orig_dst_addr(&self, _: bool) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn recv_orig_dst_addr(&self) -> io::Result<bool> {
+        Err(sys::unsupported())
+    }
+
+    pub fn recv_from_with_dst(&self, _: &mut [u8])
+                              -> io::Result<(usize, SocketAddr, Option<SocketAddr>)> {
+        Err(sys::unsupported())
+    }
+
     pub fn recv_from(&self, mut buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
         let mut me = self.inner();
         match mem::replace(&mut me.read, State::Empty) {
@@ -227,6 +240,42 @@ impl UdpSocket {
         self.imp.inner.socket.ttl()
     }
 
+    pub fn set_tos(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn tos(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_tclass_v6(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn tclass_v6(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_mark(&self, _: u32) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn mark(&self) -> io::Result<u32> {
+        Err(sys::unsupported())
+    }
+
+    pub fn bind_device(&self, _: Option<&[u8]>) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn set_freebind(&self, _: bool) -> io::Result<()> {
+        Err(sys::unsupported())
+    }
+
+    pub fn freebind(&self) -> io::Result<bool> {
+        Err(sys::unsupported())
+    }
+
     pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
         self.imp.inner.socket.set_ttl(ttl)
     }
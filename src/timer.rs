@@ -4,10 +4,11 @@
 
 use {convert, io, Ready, Poll, PollOpt, Registration, SetReadiness, Token};
 use event::Evented;
-use lazycell::LazyCell;
+use lazycell::{LazyCell, AtomicLazyCell};
 use slab::Slab;
-use std::{cmp, error, fmt, u64, usize, iter, thread};
-use std::sync::Arc;
+use std::{cmp, error, fmt, mem, u64, usize, iter, thread};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 
@@ -16,8 +17,10 @@ use self::TimerErrorKind::TimerOverflow;
 pub struct Timer<T> {
     // Size of each tick in milliseconds
     tick_ms: u64,
-    // Slab of timeout entries
+    // Slab of timeout entries, grown on demand
     entries: Slab<Entry<T>>,
+    // Max number of timeouts that can be in flight, if bounded
+    max_capacity: Option<usize>,
     // Timeout wheel. Each tick, the timer will look at the next slot for
     // timeouts that match the current tick.
     wheel: Vec<WheelEntry>,
@@ -31,6 +34,10 @@ pub struct Timer<T> {
     mask: u64,
     // Set on registration with Poll
     inner: LazyCell<Inner>,
+    // State shared with `Handle`s
+    shared: Arc<Shared<T>>,
+    // Maps `RemoteTimeout` ids to their entries
+    remote: HashMap<usize, Token>,
 }
 
 pub struct Builder {
@@ -38,8 +45,10 @@ pub struct Builder {
     tick: Duration,
     // Number of slots in the timer wheel
     num_slots: usize,
-    // Max number of timeouts that can be in flight at a given time.
+    // Number of timeout entries to allocate up front.
     capacity: usize,
+    // Max number of timeouts that can be in flight at a given time.
+    max_capacity: Option<usize>,
 }
 
 #[derive(Clone, Debug)]
@@ -50,6 +59,28 @@ pub struct Timeout {
     tick: u64,
 }
 
+/// Sets and cancels timeouts on a `Timer` from any thread.
+///
+/// Returned by `Timer::handle`. Timeouts set through a handle are moved into
+/// the timer on its next call to `poll`, and are not subject to
+/// `Builder::max_capacity`.
+pub struct Handle<T> {
+    shared: Weak<Shared<T>>,
+}
+
+/// Reference to a timeout set through a `Handle`.
+#[derive(Clone, Debug)]
+pub struct RemoteTimeout {
+    id: usize,
+}
+
+/// Iterator returned by `Timer::drain`.
+pub struct Drain<'a, T: 'a> {
+    timer: &'a mut Timer<T>,
+    // Wheel slot currently being drained
+    slot: usize,
+}
+
 struct Inner {
     registration: Registration,
     set_readiness: SetReadiness,
@@ -57,6 +88,26 @@ struct Inner {
     wakeup_thread: thread::JoinHandle<()>,
 }
 
+struct Shared<T> {
+    // Operations requested through handles, applied on the next poll
+    ops: Mutex<Vec<Op<T>>>,
+    // Used to assign `RemoteTimeout` ids
+    next_id: AtomicUsize,
+    // Tick at which the wakeup thread will next set readiness
+    wakeup_state: WakeupState,
+    // Set on registration with Poll
+    wakeup_thread: AtomicLazyCell<thread::Thread>,
+    // Tick 0's time instant
+    start: Instant,
+    // Size of each tick in milliseconds
+    tick_ms: u64,
+}
+
+enum Op<T> {
+    Set { id: usize, tick: Tick, state: T },
+    Cancel(usize),
+}
+
 impl Drop for Inner {
     fn drop(&mut self) {
         // 1. Set wakeup state to TERMINATE_THREAD (https://github.com/carllerche/mio/blob/master/src/timer.rs#L451)
@@ -77,6 +128,8 @@ struct WheelEntry {
 struct Entry<T> {
     state: T,
     links: EntryLinks,
+    // Set if the timeout came from a `Handle`
+    remote: Option<usize>,
 }
 
 #[derive(Copy, Clone)]
@@ -98,7 +151,8 @@ pub type Result<T> = ::std::result::Result<T, TimerError>;
 pub type TimerResult<T> = Result<T>;
 
 
-/// Deprecated and unused.
+/// Returned when setting a timeout on a timer that has reached the bound set
+/// by `Builder::max_capacity`.
 #[derive(Debug)]
 pub struct TimerError;
 
@@ -125,13 +179,25 @@ impl Builder {
         self
     }
 
+    /// Sets the number of timeout entries allocated up front. The timer grows
+    /// past this as needed.
     pub fn capacity(mut self, capacity: usize) -> Builder {
         self.capacity = capacity;
         self
     }
 
+    /// Bounds the number of timeouts that can be in flight at a given time.
+    /// Once reached, `Timer::set_timeout` returns `TimerError` until a
+    /// timeout fires or is canceled.
+    ///
+    /// By default the timer is unbounded.
+    pub fn max_capacity(mut self, max_capacity: usize) -> Builder {
+        self.max_capacity = Some(max_capacity);
+        self
+    }
+
     pub fn build<T>(self) -> Timer<T> {
-        Timer::new(convert::millis(self.tick), self.num_slots, self.capacity, Instant::now())
+        Timer::new(convert::millis(self.tick), self.num_slots, self.capacity, self.max_capacity, Instant::now())
     }
 }
 
@@ -141,14 +207,18 @@ impl Default for Builder {
             tick: Duration::from_millis(100),
             num_slots: 256,
             capacity: 65_536,
+            max_capacity: None,
         }
     }
 }
 
 impl<T> Timer<T> {
-    fn new(tick_ms: u64, num_slots: usize, capacity: usize, start: Instant) -> Timer<T> {
+    fn new(tick_ms: u64, num_slots: usize, capacity: usize, max_capacity: Option<usize>, start: Instant) -> Timer<T> {
         let num_slots = num_slots.next_power_of_two();
-        let capacity = capacity.next_power_of_two();
+        let capacity = match max_capacity {
+            Some(max) => cmp::min(capacity.next_power_of_two(), max),
+            None => capacity.next_power_of_two(),
+        };
         let mask = (num_slots as u64) - 1;
         let wheel = iter::repeat(WheelEntry { next_tick: TICK_MAX, head: EMPTY })
             .take(num_slots).collect();
@@ -156,15 +226,30 @@ impl<T> Timer<T> {
         Timer {
             tick_ms: tick_ms,
             entries: Slab::with_capacity(capacity),
+            max_capacity: max_capacity,
             wheel: wheel,
             start: start,
             tick: 0,
             next: EMPTY,
             mask: mask,
             inner: LazyCell::new(),
+            shared: Arc::new(Shared {
+                ops: Mutex::new(Vec::new()),
+                next_id: AtomicUsize::new(0),
+                wakeup_state: Arc::new(AtomicUsize::new(usize::MAX)),
+                wakeup_thread: AtomicLazyCell::new(),
+                start: start,
+                tick_ms: tick_ms,
+            }),
+            remote: HashMap::new(),
         }
     }
 
+    /// Returns a handle that can set and cancel timeouts from other threads.
+    pub fn handle(&self) -> Handle<T> {
+        Handle { shared: Arc::downgrade(&self.shared) }
+    }
+
     pub fn set_timeout(&mut self, delay_from_now: Duration, state: T) -> Result<Timeout> {
         let delay_from_start = self.start.elapsed() + delay_from_now;
         self.set_timeout_at(delay_from_start, state)
@@ -183,6 +268,17 @@ impl<T> Timer<T> {
     }
 
     fn insert(&mut self, tick: Tick, state: T) -> Result<Timeout> {
+        if let Some(max) = self.max_capacity {
+            if self.entries.len() >= max {
+                trace!("timer at capacity; max_capacity={}", max);
+                return Err(TimerError);
+            }
+        }
+
+        Ok(self.link(tick, state))
+    }
+
+    fn link(&mut self, tick: Tick, state: T) -> Timeout {
         // Get the slot for the requested tick
         let slot = (tick & self.mask) as usize;
         let curr = self.wheel[slot];
@@ -208,10 +304,10 @@ impl<T> Timer<T> {
         trace!("inserted timeout; slot={}; token={:?}", slot, token);
 
         // Return the new timeout
-        Ok(Timeout {
+        Timeout {
             token: token,
             tick: tick
-        })
+        }
     }
 
     pub fn cancel_timeout(&mut self, timeout: &Timeout) -> Option<T> {
@@ -226,14 +322,78 @@ impl<T> Timer<T> {
         }
 
         self.unlink(&links, timeout.token);
-        Some(self.entries.remove(timeout.token.into()).state)
+        Some(self.remove(timeout.token))
     }
 
     pub fn poll(&mut self) -> Option<T> {
+        self.process_remote();
+
         let target_tick = current_tick(self.start, self.tick_ms);
         self.poll_to(target_tick)
     }
 
+    /// Returns the instant at which the next pending timeout fires, or `None`
+    /// if there are no pending timeouts.
+    ///
+    /// The returned instant is rounded to the timer's tick granularity and may
+    /// be earlier than the actual deadline, so it is suitable for computing a
+    /// `Poll::poll` timeout.
+    ///
+    /// Timeouts set through a [`Handle`] are not taken into account until the
+    /// timer next processes them, on the next call to `poll` or `drain`.
+    ///
+    /// [`Handle`]: struct.Handle.html
+    pub fn next_deadline(&self) -> Option<Instant> {
+        if self.entries.is_empty() {
+            return None;
+        }
+
+        match self.next_tick() {
+            Some(TICK_MAX) | None => None,
+            Some(tick) => {
+                let ms = tick.saturating_mul(self.tick_ms);
+                Some(self.start + Duration::from_millis(ms))
+            }
+        }
+    }
+
+    /// Returns the number of pending timeouts.
+    ///
+    /// As with [`next_deadline`], timeouts set or canceled through a handle
+    /// are only counted once the timer has processed them.
+    ///
+    /// [`next_deadline`]: #method.next_deadline
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no pending timeouts.
+    ///
+    /// See [`len`] for how timeouts set through a handle are counted.
+    ///
+    /// [`len`]: #method.len
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Cancels all pending timeouts, returning an iterator over their states.
+    ///
+    /// Any timeouts not consumed from the iterator are dropped when the
+    /// iterator is dropped.
+    pub fn drain(&mut self) -> Drain<'_, T> {
+        self.process_remote();
+
+        if let Some(inner) = self.inner.borrow() {
+            trace!("unsetting readiness");
+            let _ = inner.set_readiness.set_readiness(Ready::empty());
+        }
+
+        Drain {
+            timer: self,
+            slot: 0,
+        }
+    }
+
     fn poll_to(&mut self, mut target_tick: Tick) -> Option<T> {
         trace!("tick_to; target_tick={}; current_tick={}", target_tick, self.tick);
 
@@ -276,7 +436,7 @@ impl<T> Timer<T> {
                     self.unlink(&links, curr);
 
                     // Remove and return the token
-                    return Some(self.entries.remove(curr.into()).state);
+                    return Some(self.remove(curr));
                 } else {
                     let next_tick = self.wheel[slot].next_tick;
                     self.wheel[slot].next_tick = cmp::min(next_tick, links.tick);
@@ -290,6 +450,12 @@ impl<T> Timer<T> {
             trace!("unsetting readiness");
             let _ = inner.set_readiness.set_readiness(Ready::empty());
 
+            // A handle may have queued a timeout after it was processed above
+            // and before readiness was unset.
+            if !self.shared.ops.lock().unwrap().is_empty() {
+                let _ = inner.set_readiness.set_readiness(Ready::readable());
+            }
+
             if let Some(tick) = self.next_tick() {
                 self.schedule_readiness(tick);
             }
@@ -298,6 +464,44 @@ impl<T> Timer<T> {
         None
     }
 
+    // Moves timeouts set or canceled through handles into the wheel
+    fn process_remote(&mut self) {
+        let ops = mem::replace(&mut *self.shared.ops.lock().unwrap(), Vec::new());
+
+        for op in ops {
+            match op {
+                Op::Set { id, mut tick, state } => {
+                    // Always target at least 1 tick in the future
+                    if tick <= self.tick {
+                        tick = self.tick + 1;
+                    }
+
+                    let timeout = self.link(tick, state);
+                    self.entries[timeout.token.into()].remote = Some(id);
+                    self.remote.insert(id, timeout.token);
+                }
+                Op::Cancel(id) => {
+                    if let Some(token) = self.remote.get(&id).cloned() {
+                        trace!("canceling remote timeout; id={}; token={:?}", id, token);
+                        let links = self.entries[token.into()].links;
+                        self.unlink(&links, token);
+                        self.remove(token);
+                    }
+                }
+            }
+        }
+    }
+
+    fn remove(&mut self, token: Token) -> T {
+        let entry = self.entries.remove(token.into());
+
+        if let Some(id) = entry.remote {
+            self.remote.remove(&id);
+        }
+
+        entry.state
+    }
+
     fn unlink(&mut self, links: &EntryLinks, token: Token) {
        trace!("unlinking timeout; slot={}; token={:?}",
                self.slot_for(links.tick), token);
@@ -321,30 +525,8 @@ impl<T> Timer<T> {
     }
 
     fn schedule_readiness(&self, tick: Tick) {
-        if let Some(inner) = self.inner.borrow() {
-            // Coordinate setting readiness w/ the wakeup thread
-            let mut curr = inner.wakeup_state.load(Ordering::Acquire);
-
-            loop {
-                if curr as Tick <= tick {
-                    // Nothing to do, wakeup is already scheduled
-                    return;
-                }
-
-                // Attempt to move the wakeup time forward
-                trace!("advancing the wakeup time; target={}; curr={}", tick, curr);
-                let actual = inner.wakeup_state.compare_and_swap(curr, tick as usize, Ordering::Release);
-
-                if actual == curr {
-                    // Signal to the wakeup thread that the wakeup time has
-                    // been changed.
-                    trace!("unparking wakeup thread");
-                    inner.wakeup_thread.thread().unpark();
-                    return;
-                }
-
-                curr = actual;
-            }
+        if self.inner.borrow().is_some() {
+            self.shared.schedule_wakeup(tick);
         }
     }
 
@@ -367,6 +549,134 @@ impl<T> Timer<T> {
     }
 }
 
+impl<'a, T> Iterator for Drain<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        while self.slot < self.timer.wheel.len() {
+            let head = self.timer.wheel[self.slot].head;
+
+            if head != EMPTY {
+                let links = self.timer.entries[head.into()].links;
+
+                // Unlink will also advance the slot's head
+                self.timer.unlink(&links, head);
+
+                return Some(self.timer.remove(head));
+            }
+
+            self.timer.wheel[self.slot].next_tick = TICK_MAX;
+            self.slot += 1;
+        }
+
+        None
+    }
+}
+
+impl<'a, T> Drop for Drain<'a, T> {
+    fn drop(&mut self) {
+        for _ in self.by_ref() {}
+    }
+}
+
+impl<T> Handle<T> {
+    /// Sets a timeout on the timer. If it is earlier than the timer's current
+    /// wakeup time, the timer's registration is woken up when it expires.
+    ///
+    /// If the timer has been dropped, `state` is dropped.
+    pub fn set_timeout(&self, delay_from_now: Duration, state: T) -> RemoteTimeout {
+        let shared = match self.shared.upgrade() {
+            Some(shared) => shared,
+            None => return RemoteTimeout { id: usize::MAX },
+        };
+
+        let id = shared.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut tick = duration_to_tick(shared.start.elapsed() + delay_from_now, shared.tick_ms);
+        trace!("setting remote timeout; id={}; tick={:?}", id, tick);
+
+        // Always target at least 1 tick in the future. This also keeps the
+        // wakeup thread from mistaking tick 0 for `TERMINATE_THREAD`.
+        let now = current_tick(shared.start, shared.tick_ms);
+
+        if tick <= now {
+            tick = now + 1;
+        }
+
+        shared.ops.lock().unwrap().push(Op::Set {
+            id: id,
+            tick: tick,
+            state: state,
+        });
+
+        shared.schedule_wakeup(tick);
+
+        RemoteTimeout { id: id }
+    }
+
+    /// Cancels a timeout previously set through a handle of the same timer.
+    ///
+    /// The timeout's state is dropped.
+    pub fn cancel_timeout(&self, timeout: &RemoteTimeout) {
+        let shared = match self.shared.upgrade() {
+            Some(shared) => shared,
+            None => return,
+        };
+
+        let mut ops = shared.ops.lock().unwrap();
+
+        let pos = ops.iter().position(|op| match *op {
+            Op::Set { id, .. } => id == timeout.id,
+            Op::Cancel(_) => false,
+        });
+
+        match pos {
+            // The timer has not seen the timeout yet
+            Some(pos) => {
+                let op = ops.remove(pos);
+                drop(ops);
+                drop(op);
+            }
+            None => ops.push(Op::Cancel(timeout.id)),
+        }
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Handle<T> {
+        Handle { shared: self.shared.clone() }
+    }
+}
+
+impl<T> Shared<T> {
+    fn schedule_wakeup(&self, tick: Tick) {
+        // Coordinate setting readiness w/ the wakeup thread
+        let mut curr = self.wakeup_state.load(Ordering::Acquire);
+
+        loop {
+            if curr as Tick <= tick {
+                // Nothing to do, wakeup is already scheduled
+                return;
+            }
+
+            // Attempt to move the wakeup time forward
+            trace!("advancing the wakeup time; target={}; curr={}", tick, curr);
+            let actual = self.wakeup_state.compare_and_swap(curr, tick as usize, Ordering::Release);
+
+            if actual == curr {
+                // Signal to the wakeup thread that the wakeup time has
+                // been changed.
+                if let Some(thread) = self.wakeup_thread.borrow() {
+                    trace!("unparking wakeup thread");
+                    thread.unpark();
+                }
+                return;
+            }
+
+            curr = actual;
+        }
+    }
+}
+
 impl<T> Default for Timer<T> {
     fn default() -> Timer<T> {
         Builder::default().build()
@@ -380,12 +690,18 @@ impl<T> Evented for Timer<T> {
         }
 
         let (registration, set_readiness) = Registration::new(poll, token, interest, opts);
-        let wakeup_state = Arc::new(AtomicUsize::new(usize::MAX));
+        let wakeup_state = self.shared.wakeup_state.clone();
         let thread_handle = spawn_wakeup_thread(
             wakeup_state.clone(),
             set_readiness.clone(),
             self.start, self.tick_ms);
 
+        let _ = self.shared.wakeup_thread.fill(thread_handle.thread().clone());
+
+        // A handle may have moved the wakeup time forward before the thread
+        // could be unparked.
+        thread_handle.thread().unpark();
+
         self.inner.fill(Inner {
             registration: registration,
             set_readiness: set_readiness,
@@ -489,21 +805,20 @@ impl<T> Entry<T> {
                 prev: EMPTY,
                 next: next,
             },
+            remote: None,
         }
     }
 }
 
 impl fmt::Display for TimerError {
-    fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
-        // `TimerError` will never be constructed.
-        unreachable!();
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", TimerOverflow)
     }
 }
 
 impl error::Error for TimerError {
     fn description(&self) -> &str {
-        // `TimerError` will never be constructed.
-        unreachable!();
+        "timer overflow"
     }
 }
 
@@ -37,9 +37,13 @@ mod test_write_then_drop;
 #[cfg(feature = "with-deprecated")]
 mod test_notify;
 #[cfg(feature = "with-deprecated")]
+mod test_poll_broadcast;
+#[cfg(feature = "with-deprecated")]
 mod test_poll_channel;
 #[cfg(feature = "with-deprecated")]
 mod test_tick;
+#[cfg(feature = "with-deprecated")]
+mod test_timer;
 
 // The following tests are for deprecated features. Only run these tests on
 // platforms that were supported from before the features were deprecated
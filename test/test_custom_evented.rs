@@ -21,6 +21,30 @@ fn smoke() {
     assert_eq!(events.get(0).unwrap().token(), Token(0));
 }
 
+#[test]
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn priority_readiness() {
+    use mio::unix::UnixReady;
+
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(128);
+
+    let (r, set) = Registration::new2();
+    let interest = Ready::readable() | UnixReady::priority();
+    r.register(&poll, Token(0), interest, PollOpt::edge()).unwrap();
+
+    set.set_readiness(UnixReady::priority().into()).unwrap();
+    assert!(UnixReady::from(set.readiness()).is_priority());
+
+    let n = poll.poll(&mut events, Some(Duration::from_millis(0))).unwrap();
+    assert_eq!(n, 1);
+
+    let event = events.get(0).unwrap();
+    assert_eq!(event.token(), Token(0));
+    assert!(UnixReady::from(event.readiness()).is_priority());
+    assert!(!event.readiness().is_writable());
+}
+
 #[test]
 fn set_readiness_before_register() {
     use std::sync::{Arc, Barrier};
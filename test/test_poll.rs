@@ -1,5 +1,7 @@
 use mio::*;
-use std::time::Duration;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 #[test]
 fn test_poll_closes_fd() {
@@ -16,3 +18,72 @@ fn test_poll_closes_fd() {
         drop(registration);
     }
 }
+
+#[test]
+fn test_poll_deadline() {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let start = Instant::now();
+    poll.register_deadline(Token(1), start + Duration::from_millis(300)).unwrap();
+    poll.register_deadline(Token(0), start + Duration::from_millis(100)).unwrap();
+
+    // The earliest deadline shrinks the timeout
+    poll.poll(&mut events, None).unwrap();
+    let tokens: Vec<_> = events.iter().map(|e| e.token()).collect();
+    assert_eq!(vec![Token(0)], tokens);
+    assert!(events.iter().next().unwrap().readiness().is_readable());
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+    let tokens: Vec<_> = events.iter().map(|e| e.token()).collect();
+    assert_eq!(vec![Token(1)], tokens);
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // Deadlines only fire once
+    poll.poll(&mut events, Some(Duration::from_millis(100))).unwrap();
+    assert!(events.is_empty());
+}
+
+#[test]
+fn test_poll_deadline_replace_and_deregister() {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+
+    let now = Instant::now();
+    poll.register_deadline(Token(0), now).unwrap();
+    poll.register_deadline(Token(0), now + Duration::from_secs(60)).unwrap();
+    poll.register_deadline(Token(1), now).unwrap();
+
+    assert!(poll.deregister_deadline(Token(1)));
+    assert!(!poll.deregister_deadline(Token(1)));
+
+    poll.poll(&mut events, Some(Duration::from_millis(100))).unwrap();
+    assert!(events.is_empty());
+}
+
+#[test]
+fn test_poll_deadline_from_other_thread() {
+    let poll = Arc::new(Poll::new().unwrap());
+    let mut events = Events::with_capacity(16);
+
+    let start = Instant::now();
+    let p = poll.clone();
+    let th = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        p.register_deadline(Token(0), Instant::now() + Duration::from_millis(50)).unwrap();
+    });
+
+    loop {
+        // The registering thread must wake up this call for the test to
+        // finish in time.
+        poll.poll(&mut events, Some(Duration::from_secs(10))).unwrap();
+
+        if events.iter().any(|e| e.token() == Token(0)) {
+            break;
+        }
+    }
+
+    assert!(start.elapsed() < Duration::from_secs(5));
+    th.join().unwrap();
+}
@@ -0,0 +1,160 @@
+use mio::{channel, Events, Poll, PollOpt, Ready, Token};
+use mio::channel::BroadcastTryRecvError;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+pub fn test_broadcast_every_subscriber_receives() {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(1024);
+    let (tx, rx1) = channel::broadcast(16);
+    let rx2 = tx.subscribe();
+
+    poll.register(&rx1, Token(1), Ready::readable(), PollOpt::edge()).unwrap();
+    poll.register(&rx2, Token(2), Ready::readable(), PollOpt::edge()).unwrap();
+
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(0, num);
+
+    tx.send("hello").unwrap();
+
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(2, num);
+
+    for event in &events {
+        assert_eq!(event.readiness(), Ready::readable());
+    }
+
+    assert_eq!("hello", rx1.try_recv().unwrap());
+    assert_eq!("hello", rx2.try_recv().unwrap());
+
+    match rx1.try_recv() {
+        Err(BroadcastTryRecvError::Empty) => {}
+        no => panic!("unexpected value {:?}", no),
+    }
+
+    // A late subscriber only sees values sent after subscribing
+    let rx3 = tx.subscribe();
+    tx.send("goodbye").unwrap();
+
+    assert_eq!("goodbye", rx3.try_recv().unwrap());
+    assert_eq!("goodbye", rx1.try_recv().unwrap());
+}
+
+#[test]
+pub fn test_broadcast_lagged() {
+    let (tx, rx) = channel::broadcast(2);
+
+    for i in 0..5 {
+        tx.send(i).unwrap();
+    }
+
+    match rx.try_recv() {
+        Err(BroadcastTryRecvError::Lagged(3)) => {}
+        no => panic!("unexpected value {:?}", no),
+    }
+
+    assert_eq!(3, rx.try_recv().unwrap());
+    assert_eq!(4, rx.try_recv().unwrap());
+}
+
+#[test]
+pub fn test_broadcast_level_readiness_cleared_when_caught_up() {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(1024);
+    let (tx, rx) = channel::broadcast(4);
+
+    poll.register(&rx, Token(0), Ready::readable(), PollOpt::level()).unwrap();
+
+    tx.send(1).unwrap();
+    tx.send(2).unwrap();
+
+    assert_eq!(1, rx.try_recv().unwrap());
+
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(1, num);
+
+    assert_eq!(2, rx.try_recv().unwrap());
+
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(0, num);
+}
+
+#[test]
+pub fn test_broadcast_disconnect() {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(1024);
+    let (tx, rx) = channel::broadcast(4);
+
+    poll.register(&rx, Token(0), Ready::readable(), PollOpt::edge()).unwrap();
+
+    tx.send("last").unwrap();
+    drop(tx);
+
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(1, num);
+    assert_eq!(events.get(0).unwrap().readiness(), Ready::readable() | Ready::hup());
+
+    // Buffered values are still received
+    assert_eq!("last", rx.try_recv().unwrap());
+
+    match rx.try_recv() {
+        Err(BroadcastTryRecvError::Disconnected) => {}
+        no => panic!("unexpected value {:?}", no),
+    }
+
+    // Sending without subscribers fails
+    let (tx, rx) = channel::broadcast(4);
+    drop(rx);
+
+    assert!(tx.send("nobody").is_err());
+}
+
+#[test]
+pub fn test_broadcast_to_other_threads() {
+    const THREADS: usize = 4;
+    const MESSAGES: usize = 100;
+
+    let (tx, rx) = channel::broadcast(MESSAGES);
+    let mut receivers = vec![rx];
+
+    for _ in 1..THREADS {
+        receivers.push(tx.subscribe());
+    }
+
+    let handles: Vec<_> = receivers.into_iter().map(|rx| {
+        thread::spawn(move || {
+            let poll = Poll::new().unwrap();
+            let mut events = Events::with_capacity(1024);
+            let mut next = 0;
+
+            poll.register(&rx, Token(0), Ready::readable(), PollOpt::edge()).unwrap();
+
+            loop {
+                poll.poll(&mut events, None).unwrap();
+
+                loop {
+                    match rx.try_recv() {
+                        Ok(i) => {
+                            assert_eq!(next, i);
+                            next += 1;
+                        }
+                        Err(BroadcastTryRecvError::Empty) => break,
+                        Err(BroadcastTryRecvError::Disconnected) => return next,
+                        Err(e) => panic!("unexpected error {:?}", e),
+                    }
+                }
+            }
+        })
+    }).collect();
+
+    for i in 0..MESSAGES {
+        tx.send(i).unwrap();
+    }
+
+    drop(tx);
+
+    for handle in handles {
+        assert_eq!(MESSAGES, handle.join().unwrap());
+    }
+}
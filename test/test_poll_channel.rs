@@ -61,7 +61,7 @@ pub fn test_poll_channel_edge() {
 
     let event = events.get(0).unwrap();
     assert_eq!(event.token(), Token(123));
-    assert_eq!(event.readiness(), Ready::readable());
+    assert_eq!(event.readiness(), Ready::readable() | Ready::hup());
 
     match rx.try_recv() {
         Err(TryRecvError::Disconnected) => {}
@@ -283,3 +283,433 @@ pub fn test_sending_from_other_thread_while_polling() {
         }
     }
 }
+
+#[test]
+pub fn test_poll_sync_channel_sender_writable() {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(1024);
+    let (tx, rx) = channel::sync_channel(2);
+
+    poll.register(&tx, Token(1), Ready::writable(), PollOpt::edge()).unwrap();
+
+    // The channel starts with spare capacity
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(1, num);
+
+    let event = events.get(0).unwrap();
+    assert_eq!(event.token(), Token(1));
+    assert!(event.readiness().is_writable());
+
+    // Fill the channel
+    tx.try_send("hello").unwrap();
+    tx.try_send("world").unwrap();
+
+    match tx.try_send("full") {
+        Err(channel::TrySendError::Full(_)) => {}
+        no => panic!("unexpected value {:?}", no),
+    }
+
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(0, num);
+
+    // Receiving a value frees up capacity
+    assert_eq!("hello", rx.try_recv().unwrap());
+
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(1, num);
+
+    let event = events.get(0).unwrap();
+    assert_eq!(event.token(), Token(1));
+    assert!(event.readiness().is_writable());
+
+    tx.try_send("again").unwrap();
+}
+
+#[test]
+pub fn test_poll_sync_channel_single_registered_sender() {
+    let poll = Poll::new().unwrap();
+    let (tx, _rx) = channel::sync_channel::<u32>(2);
+    let tx2 = tx.clone();
+
+    poll.register(&tx, Token(1), Ready::writable(), PollOpt::edge()).unwrap();
+
+    // A clone cannot be registered, and is left unregistered
+    assert!(poll.register(&tx2, Token(2), Ready::writable(), PollOpt::edge()).is_err());
+    assert!(poll.reregister(&tx2, Token(2), Ready::writable(), PollOpt::edge()).is_err());
+    assert!(poll.deregister(&tx2).is_err());
+
+    poll.reregister(&tx, Token(1), Ready::writable(), PollOpt::edge()).unwrap();
+}
+
+#[test]
+pub fn test_poll_sync_channel_sender_from_other_thread() {
+    const MESSAGES: usize = 100;
+
+    let (tx, rx) = channel::sync_channel(4);
+
+    let th = thread::spawn(move || {
+        let poll = Poll::new().unwrap();
+        let mut events = Events::with_capacity(1024);
+
+        poll.register(&tx, Token(0), Ready::writable(), PollOpt::edge()).unwrap();
+
+        let mut sent = 0;
+
+        while sent < MESSAGES {
+            poll.poll(&mut events, None).unwrap();
+
+            loop {
+                match tx.try_send(sent) {
+                    Ok(()) => sent += 1,
+                    Err(channel::TrySendError::Full(_)) => break,
+                    Err(e) => panic!("unexpected error {:?}", e),
+                }
+
+                if sent == MESSAGES {
+                    break;
+                }
+            }
+        }
+    });
+
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(1024);
+
+    poll.register(&rx, Token(0), Ready::readable(), PollOpt::edge()).unwrap();
+
+    let mut recv = 0;
+
+    while recv < MESSAGES {
+        poll.poll(&mut events, None).unwrap();
+
+        while let Ok(n) = rx.try_recv() {
+            assert_eq!(recv, n);
+            recv += 1;
+        }
+    }
+
+    th.join().unwrap();
+}
+
+#[test]
+pub fn test_poll_channel_hup_with_pending_messages() {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(1024);
+    let (tx, rx) = channel::channel();
+
+    poll.register(&rx, Token(0), Ready::readable(), PollOpt::level()).unwrap();
+
+    tx.send("hello").unwrap();
+    drop(tx);
+
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(1, num);
+    assert_eq!(events.get(0).unwrap().readiness(), Ready::readable() | Ready::hup());
+
+    // Draining the channel does not clear hup
+    assert_eq!("hello", rx.try_recv().unwrap());
+
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(1, num);
+    assert_eq!(events.get(0).unwrap().readiness(), Ready::readable() | Ready::hup());
+
+    match rx.try_recv() {
+        Err(TryRecvError::Disconnected) => {}
+        no => panic!("unexpected value {:?}", no),
+    }
+}
+
+#[test]
+pub fn test_poll_channel_register_after_disconnect() {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(1024);
+    let (tx, rx) = channel::channel::<()>();
+
+    drop(tx);
+
+    poll.register(&rx, Token(0), Ready::readable(), PollOpt::edge()).unwrap();
+
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(1, num);
+    assert_eq!(events.get(0).unwrap().readiness(), Ready::readable() | Ready::hup());
+}
+
+#[test]
+pub fn test_poll_sync_channel_sender_hup() {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(1024);
+    let (tx, rx) = channel::sync_channel(1);
+
+    poll.register(&tx, Token(0), Ready::writable(), PollOpt::edge()).unwrap();
+    tx.try_send("hello").unwrap();
+
+    // Drain the initial writable event
+    poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+
+    drop(rx);
+
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(1, num);
+
+    let event = events.get(0).unwrap();
+    assert_eq!(event.token(), Token(0));
+    assert!(event.readiness().is_writable());
+    assert!(event.readiness().contains(Ready::hup()));
+
+    match tx.try_send("goodbye") {
+        Err(channel::TrySendError::Disconnected(_)) => {}
+        no => panic!("unexpected value {:?}", no),
+    }
+}
+
+#[test]
+pub fn test_poll_channel_try_recv_many() {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(1024);
+    let (tx, rx) = channel::channel();
+
+    poll.register(&rx, Token(0), Ready::readable(), PollOpt::level()).unwrap();
+
+    for i in 0..10 {
+        tx.send(i).unwrap();
+    }
+
+    let mut values = vec![];
+    assert_eq!(4, rx.try_recv_many(&mut values, 4).unwrap());
+    assert_eq!(vec![0, 1, 2, 3], values);
+
+    // Still readable while values remain
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(1, num);
+
+    assert_eq!(6, rx.try_recv_many(&mut values, 100).unwrap());
+    assert_eq!((0..10).collect::<Vec<_>>(), values);
+
+    // Drained, so no longer readable
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(0, num);
+
+    match rx.try_recv_many(&mut values, 100) {
+        Err(TryRecvError::Empty) => {}
+        no => panic!("unexpected value {:?}", no),
+    }
+
+    drop(tx);
+
+    match rx.try_recv_many(&mut values, 100) {
+        Err(TryRecvError::Disconnected) => {}
+        no => panic!("unexpected value {:?}", no),
+    }
+}
+
+#[test]
+pub fn test_poll_channel_send_after_receiver_dropped() {
+    let (tx, rx) = channel::channel();
+
+    drop(rx);
+
+    match tx.send("hello") {
+        Err(channel::SendError::Disconnected("hello")) => {}
+        no => panic!("unexpected value {:?}", no),
+    }
+}
+
+#[test]
+pub fn test_poll_channel_preserves_order_per_sender() {
+    const THREADS: usize = 4;
+    const MESSAGES: usize = 1_000;
+
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(1024);
+    let (tx, rx) = channel::channel();
+
+    poll.register(&rx, Token(0), Ready::readable(), PollOpt::edge()).unwrap();
+
+    for t in 0..THREADS {
+        let tx = tx.clone();
+
+        thread::spawn(move || {
+            for i in 0..MESSAGES {
+                tx.send((t, i)).unwrap();
+            }
+        });
+    }
+
+    drop(tx);
+
+    let mut next = vec![0; THREADS];
+    let mut values = vec![];
+
+    'outer:
+    loop {
+        poll.poll(&mut events, None).unwrap();
+
+        loop {
+            values.clear();
+
+            match rx.try_recv_many(&mut values, 64) {
+                Ok(_) => {
+                    for &(t, i) in &values {
+                        assert_eq!(next[t], i);
+                        next[t] += 1;
+                    }
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => break 'outer,
+            }
+        }
+    }
+
+    assert_eq!(vec![MESSAGES; THREADS], next);
+}
+
+#[test]
+pub fn test_poll_oneshot_channel() {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(1024);
+    let (tx, rx) = channel::oneshot();
+
+    poll.register(&rx, Token(0), Ready::readable(), PollOpt::level()).unwrap();
+
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(0, num);
+
+    match rx.try_recv() {
+        Err(TryRecvError::Empty) => {}
+        no => panic!("unexpected value {:?}", no),
+    }
+
+    thread::spawn(move || {
+        tx.send("hello").unwrap();
+    }).join().unwrap();
+
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(1, num);
+    assert_eq!(events.get(0).unwrap().readiness(), Ready::readable());
+
+    assert_eq!("hello", rx.try_recv().unwrap());
+
+    // Readable exactly once, even with level-triggered notifications
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(0, num);
+
+    match rx.try_recv() {
+        Err(TryRecvError::Disconnected) => {}
+        no => panic!("unexpected value {:?}", no),
+    }
+}
+
+#[test]
+pub fn test_poll_oneshot_channel_sender_dropped() {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(1024);
+    let (tx, rx) = channel::oneshot::<()>();
+
+    poll.register(&rx, Token(0), Ready::readable(), PollOpt::edge()).unwrap();
+
+    drop(tx);
+
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(1, num);
+    assert_eq!(events.get(0).unwrap().readiness(), Ready::readable() | Ready::hup());
+
+    match rx.try_recv() {
+        Err(TryRecvError::Disconnected) => {}
+        no => panic!("unexpected value {:?}", no),
+    }
+}
+
+#[test]
+pub fn test_poll_oneshot_channel_receiver_dropped() {
+    let (tx, rx) = channel::oneshot();
+
+    drop(rx);
+
+    assert_eq!(Err("hello"), tx.send("hello"));
+}
+
+#[test]
+pub fn test_poll_semaphore() {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(1024);
+    let semaphore = channel::Semaphore::new(2);
+
+    poll.register(&semaphore, Token(0), Ready::readable(), PollOpt::level()).unwrap();
+
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(1, num);
+
+    assert!(!semaphore.try_acquire(3).unwrap());
+    assert!(semaphore.try_acquire(2).unwrap());
+    assert_eq!(0, semaphore.available_permits());
+
+    // No permits left, so not readable
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(0, num);
+
+    let handle = semaphore.handle();
+
+    thread::spawn(move || {
+        handle.add_permits(3).unwrap();
+    }).join().unwrap();
+
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(1, num);
+    assert_eq!(events.get(0).unwrap().readiness(), Ready::readable());
+
+    assert_eq!(3, semaphore.available_permits());
+    assert!(semaphore.try_acquire(1).unwrap());
+
+    // Still readable while permits remain
+    let num = poll.poll(&mut events, Some(Duration::from_millis(300))).unwrap();
+    assert_eq!(1, num);
+}
+
+#[test]
+pub fn test_poll_semaphore_bounds_concurrent_work() {
+    const WORK: usize = 100;
+    const MAX_IN_FLIGHT: usize = 4;
+
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(1024);
+    let semaphore = channel::Semaphore::new(MAX_IN_FLIGHT);
+
+    poll.register(&semaphore, Token(0), Ready::readable(), PollOpt::edge()).unwrap();
+
+    let (done_tx, done_rx) = ::std::sync::mpsc::channel();
+    let mut dispatched = 0;
+
+    while dispatched < WORK {
+        poll.poll(&mut events, None).unwrap();
+
+        while dispatched < WORK && semaphore.try_acquire(1).unwrap() {
+            let handle = semaphore.handle();
+            let done_tx = done_tx.clone();
+
+            thread::spawn(move || {
+                sleep_ms(1);
+                done_tx.send(()).unwrap();
+                handle.add_permits(1).unwrap();
+            });
+
+            dispatched += 1;
+            assert!(semaphore.available_permits() <= MAX_IN_FLIGHT);
+        }
+    }
+
+    for _ in 0..WORK {
+        done_rx.recv().unwrap();
+    }
+}
+
+#[test]
+pub fn test_poll_semaphore_add_permits_overflow() {
+    let semaphore = channel::Semaphore::new(::std::usize::MAX - 1);
+
+    semaphore.add_permits(1).unwrap();
+    assert!(semaphore.add_permits(1).is_err());
+    assert!(semaphore.handle().add_permits(::std::usize::MAX).is_err());
+
+    // A failed add leaves the count untouched
+    assert_eq!(::std::usize::MAX, semaphore.available_permits());
+}
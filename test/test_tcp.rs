@@ -11,7 +11,11 @@ use net2::{self, TcpStreamExt};
 use {TryRead, TryWrite};
 use mio::{Token, Ready, PollOpt, Poll, Events};
 use iovec::IoVec;
-use mio::net::{TcpListener, TcpStream};
+use mio::net::{TcpConnector, TcpListener, TcpSocket, TcpStream};
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use mio::net::TcpState;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use mio::unix::UnixReady;
 
 #[test]
 fn accept() {
@@ -658,3 +662,786 @@ fn write_error() {
         }
     }
 }
+
+fn connect_with(poll: &Poll, connector: &mut TcpConnector) -> io::Result<TcpStream> {
+    let mut events = Events::with_capacity(128);
+
+    loop {
+        poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+        assert!(!events.is_empty(), "connector timed out");
+
+        match connector.connect(poll) {
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            res => return res,
+        }
+    }
+}
+
+#[test]
+fn connector_skips_refused_address() {
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = l.local_addr().unwrap();
+
+    // Nothing listens on this port once the listener is dropped
+    let refused = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap()
+        .local_addr().unwrap();
+
+    let poll = Poll::new().unwrap();
+    let mut connector = TcpConnector::new(&[refused, addr]).unwrap();
+    connector.set_attempt_delay(Duration::from_secs(60));
+
+    poll.register(&connector, Token(1), Ready::writable(), PollOpt::edge()).unwrap();
+
+    // The refused attempt starts the next one without waiting for the delay
+    let s = connect_with(&poll, &mut connector).unwrap();
+    assert_eq!(addr, s.peer_addr().unwrap());
+    assert!(l.accept().is_ok());
+}
+
+#[test]
+fn connector_all_refused() {
+    let a = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap()
+        .local_addr().unwrap();
+    let b = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap()
+        .local_addr().unwrap();
+
+    let poll = Poll::new().unwrap();
+    let mut connector = TcpConnector::new(&[a, b]).unwrap();
+
+    poll.register(&connector, Token(1), Ready::writable(), PollOpt::edge()).unwrap();
+
+    let err = connect_with(&poll, &mut connector).unwrap_err();
+    assert_eq!(io::ErrorKind::ConnectionRefused, err.kind());
+}
+
+#[test]
+fn connector_starts_next_attempt_after_delay() {
+    // A listener with a full backlog leaves further connection attempts
+    // pending.
+    let stalled = net2::TcpBuilder::new_v4().unwrap()
+        .bind("127.0.0.1:0").unwrap()
+        .listen(0).unwrap();
+    let stalled_addr = stalled.local_addr().unwrap();
+
+    let mut fillers = Vec::new();
+    for _ in 0..8 {
+        let s = net2::TcpBuilder::new_v4().unwrap().to_tcp_stream().unwrap();
+        s.set_nonblocking(true).unwrap();
+        let _ = s.connect(&stalled_addr);
+        fillers.push(s);
+    }
+
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = l.local_addr().unwrap();
+
+    let poll = Poll::new().unwrap();
+    let mut connector = TcpConnector::new(&[stalled_addr, addr]).unwrap();
+    connector.set_attempt_delay(Duration::from_millis(100));
+
+    poll.register(&connector, Token(1), Ready::writable(), PollOpt::edge()).unwrap();
+
+    let s = connect_with(&poll, &mut connector).unwrap();
+    assert_eq!(addr, s.peer_addr().unwrap());
+}
+
+#[test]
+fn tcp_socket_listen_backlog() {
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.set_reuseaddr(true).unwrap();
+    assert!(socket.reuseaddr().unwrap());
+
+    socket.set_recv_buffer_size(64 * 1024).unwrap();
+    assert!(socket.recv_buffer_size().unwrap() >= 64 * 1024);
+
+    socket.bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let l = socket.listen(1).unwrap();
+    let addr = l.local_addr().unwrap();
+
+    let poll = Poll::new().unwrap();
+    poll.register(&l, Token(0), Ready::readable(), PollOpt::level()).unwrap();
+
+    let s = net::TcpStream::connect(&addr).unwrap();
+    let (_, peer) = accept_blocking(&poll, &l);
+    assert_eq!(s.local_addr().unwrap(), peer);
+}
+
+#[test]
+fn tcp_socket_connect_from_source_address() {
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = l.local_addr().unwrap();
+
+    let src = net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.set_reuseaddr(true).unwrap();
+    socket.bind(&src).unwrap();
+    assert_eq!(src, socket.local_addr().unwrap());
+
+    let s = socket.connect(&addr).unwrap();
+    assert_eq!(src, s.local_addr().unwrap());
+
+    let poll = Poll::new().unwrap();
+    poll.register(&l, Token(0), Ready::readable(), PollOpt::level()).unwrap();
+
+    let (_, peer) = accept_blocking(&poll, &l);
+    assert_eq!(src, peer);
+}
+
+#[cfg(unix)]
+#[test]
+fn tcp_socket_reuseport() {
+    let first = TcpSocket::new_v4().unwrap();
+    first.set_reuseport(true).unwrap();
+    assert!(first.reuseport().unwrap());
+    first.bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let first = first.listen(128).unwrap();
+    let addr = first.local_addr().unwrap();
+
+    let second = TcpSocket::new_v4().unwrap();
+    second.set_reuseport(true).unwrap();
+    second.bind(&addr).unwrap();
+    let second = second.listen(128).unwrap();
+
+    assert_eq!(addr, second.local_addr().unwrap());
+}
+
+// `l` must be registered with `poll` for readable readiness
+fn accept_blocking(poll: &Poll, l: &TcpListener) -> (TcpStream, net::SocketAddr) {
+    let mut events = Events::with_capacity(16);
+
+    loop {
+        match l.accept() {
+            Ok(ret) => return ret,
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+            }
+            Err(e) => panic!("accept failed: {}", e),
+        }
+    }
+}
+
+#[test]
+fn connect_with_data() {
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = l.local_addr().unwrap();
+
+    if cfg!(any(target_os = "linux", target_os = "android")) {
+        l.set_fastopen(16).unwrap();
+        assert!(l.fastopen().unwrap() > 0);
+    }
+
+    let data = b"hello fast open";
+
+    let l_poll = Poll::new().unwrap();
+    l_poll.register(&l, Token(0), Ready::readable(), PollOpt::level()).unwrap();
+
+    // The first connection fetches a cookie, later ones may carry data
+    for _ in 0..2 {
+        let poll = Poll::new().unwrap();
+        let mut events = Events::with_capacity(16);
+
+        let (mut s, n) = TcpStream::connect_with_data(&addr, data).unwrap();
+        assert!(n <= data.len());
+
+        poll.register(&s, Token(0), Ready::writable(), PollOpt::edge()).unwrap();
+        poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+        assert!(!events.is_empty());
+
+        s.write_all(&data[n..]).unwrap();
+
+        let (accepted, _) = accept_blocking(&l_poll, &l);
+        assert_eq!(&data[..], &read_blocking(&accepted, data.len())[..]);
+    }
+}
+
+fn read_blocking(s: &TcpStream, len: usize) -> Vec<u8> {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+    let mut buf = vec![0; len];
+    let mut n = 0;
+
+    poll.register(s, Token(0), Ready::readable(), PollOpt::level()).unwrap();
+
+    while n < len {
+        match (&*s).read(&mut buf[n..]) {
+            Ok(0) => break,
+            Ok(cnt) => n += cnt,
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+            }
+            Err(e) => panic!("read failed: {}", e),
+        }
+    }
+
+    buf.truncate(n);
+    buf
+}
+
+// Spawns a thread that accepts a single connection and reads it to the end
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn read_to_end_in_thread() -> (net::SocketAddr, thread::JoinHandle<Vec<u8>>) {
+    let l = net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = l.local_addr().unwrap();
+
+    let t = thread::spawn(move || {
+        let mut buf = Vec::new();
+        l.accept().unwrap().0.read_to_end(&mut buf).unwrap();
+        buf
+    });
+
+    (addr, t)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn send_file() {
+    use std::fs::File;
+    use tempdir::TempDir;
+
+    let data: Vec<u8> = (0..256 * 1024).map(|i| i as u8).collect();
+
+    let dir = TempDir::new("mio").unwrap();
+    let path = dir.path().join("send_file");
+    File::create(&path).unwrap().write_all(&data).unwrap();
+    let file = File::open(&path).unwrap();
+
+    let (addr, t) = read_to_end_in_thread();
+
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+    let s = TcpStream::connect(&addr).unwrap();
+
+    poll.register(&s, Token(0), Ready::writable(), PollOpt::level()).unwrap();
+
+    // Skip the first byte to check the offset is honored
+    let mut offset = 1;
+
+    loop {
+        poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+
+        match s.send_file(&file, offset, 64 * 1024) {
+            Ok(0) => break,
+            Ok(n) => offset += n as u64,
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => panic!("send_file failed: {}", e),
+        }
+    }
+
+    drop(s);
+    assert_eq!(&data[1..], &t.join().unwrap()[..]);
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn splice_between_streams() {
+    use mio::unix::SplicePipe;
+
+    const SRC: Token = Token(0);
+    const DST: Token = Token(1);
+
+    let data: Vec<u8> = (0..256 * 1024).map(|i| (i % 251) as u8).collect();
+
+    // Client writing into the proxy
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let l_addr = l.local_addr().unwrap();
+    let client_data = data.clone();
+    let client = thread::spawn(move || {
+        let mut s = net::TcpStream::connect(&l_addr).unwrap();
+        s.write_all(&client_data).unwrap();
+    });
+
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+
+    poll.register(&l, Token(2), Ready::readable(), PollOpt::level()).unwrap();
+    let (src, _) = accept_blocking(&poll, &l);
+    poll.deregister(&l).unwrap();
+
+    // Upstream reading from the proxy
+    let (addr, upstream) = read_to_end_in_thread();
+    let dst = TcpStream::connect(&addr).unwrap();
+
+    poll.register(&src, SRC, Ready::readable(), PollOpt::edge()).unwrap();
+    poll.register(&dst, DST, Ready::writable(), PollOpt::edge()).unwrap();
+
+    let mut pipe = SplicePipe::new().unwrap();
+    let mut eof = false;
+
+    while !eof || !pipe.is_empty() {
+        poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+        assert!(!events.is_empty(), "proxy stalled");
+
+        loop {
+            if !eof {
+                match pipe.splice_from(&src, 64 * 1024) {
+                    Ok(0) => eof = true,
+                    Ok(_) => {}
+                    Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                    Err(e) => panic!("splice_from failed: {}", e),
+                }
+            }
+
+            if pipe.is_empty() {
+                break;
+            }
+
+            match pipe.splice_to(&dst, 64 * 1024) {
+                Ok(_) => {}
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) => panic!("splice_to failed: {}", e),
+            }
+        }
+    }
+
+    drop(dst);
+    client.join().unwrap();
+    assert_eq!(data, upstream.join().unwrap());
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn send_zerocopy() {
+    use mio::unix::UnixReady;
+
+    let (addr, t) = read_to_end_in_thread();
+
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+    let s = TcpStream::connect(&addr).unwrap();
+
+    s.set_zerocopy(true).unwrap();
+    assert!(s.zerocopy().unwrap());
+
+    poll.register(&s, Token(0), Ready::writable(), PollOpt::edge()).unwrap();
+    poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+
+    let bufs = vec![vec![1u8; 16 * 1024], vec![2u8; 16 * 1024]];
+    let mut sent = 0;
+
+    for buf in &bufs {
+        assert_eq!(buf.len(), s.send_zerocopy(buf).unwrap());
+        sent += 1;
+    }
+
+    let mut completed = Vec::new();
+
+    while completed.len() < sent {
+        poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+
+        let ready = events.iter().fold(Ready::empty(), |r, e| r | e.readiness());
+
+        if !UnixReady::from(ready).is_error() {
+            assert!(!events.is_empty(), "no completion notification");
+            continue;
+        }
+
+        for completion in s.zerocopy_completions() {
+            let completion = completion.unwrap();
+            for n in 0..sent as u32 {
+                if completion.contains(n) {
+                    completed.push(n);
+                }
+            }
+        }
+    }
+
+    completed.sort();
+    assert_eq!(vec![0, 1], completed);
+
+    drop(s);
+    assert_eq!(32 * 1024, t.join().unwrap().len());
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn tcp_info() {
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = l.local_addr().unwrap();
+
+    let poll = Poll::new().unwrap();
+    poll.register(&l, Token(0), Ready::readable(), PollOpt::level()).unwrap();
+
+    let mut s = TcpStream::connect(&addr).unwrap();
+    let (peer, _) = accept_blocking(&poll, &l);
+
+    let mut events = Events::with_capacity(16);
+    poll.register(&s, Token(1), Ready::writable(), PollOpt::edge()).unwrap();
+    poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+
+    s.write_all(b"hello").unwrap();
+    assert_eq!(&b"hello"[..], &read_blocking(&peer, 5)[..]);
+
+    let info = s.tcp_info().unwrap();
+    assert_eq!(TcpState::Established, info.state());
+    assert!(info.snd_cwnd() > 0);
+    assert!(info.snd_mss() > 0);
+
+    let info = peer.tcp_info().unwrap();
+    assert_eq!(5, info.bytes_received());
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn keepalive_and_user_timeout() {
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let s = TcpStream::connect(&l.local_addr().unwrap()).unwrap();
+
+    s.set_keepalive(Some(Duration::from_secs(5))).unwrap();
+
+    s.set_keepalive_interval(Duration::from_secs(2)).unwrap();
+    assert_eq!(Duration::from_secs(2), s.keepalive_interval().unwrap());
+
+    // Rounded up to whole seconds
+    s.set_keepalive_interval(Duration::from_millis(1_500)).unwrap();
+    assert_eq!(Duration::from_secs(2), s.keepalive_interval().unwrap());
+
+    s.set_keepalive_retries(3).unwrap();
+    assert_eq!(3, s.keepalive_retries().unwrap());
+
+    assert_eq!(None, s.user_timeout().unwrap());
+    s.set_user_timeout(Some(Duration::from_millis(2_500))).unwrap();
+    assert_eq!(Some(Duration::from_millis(2_500)), s.user_timeout().unwrap());
+    s.set_user_timeout(None).unwrap();
+    assert_eq!(None, s.user_timeout().unwrap());
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn notsent_lowat_and_cork() {
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let l_poll = Poll::new().unwrap();
+    l_poll.register(&l, Token(0), Ready::readable(), PollOpt::level()).unwrap();
+
+    let s = TcpStream::connect(&l.local_addr().unwrap()).unwrap();
+    let (accepted, _) = accept_blocking(&l_poll, &l);
+
+    s.set_notsent_lowat(16 * 1024).unwrap();
+    assert_eq!(16 * 1024, s.notsent_lowat().unwrap());
+
+    // A fresh socket with a low watermark is still writable
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+    poll.register(&s, Token(0), Ready::writable(), PollOpt::edge()).unwrap();
+    poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+    assert!(events.iter().any(|e| e.readiness().is_writable()));
+
+    assert!(!s.corked().unwrap());
+    s.set_cork(true).unwrap();
+    assert!(s.corked().unwrap());
+    s.set_cork(false).unwrap();
+
+    {
+        let _cork = s.cork().unwrap();
+        assert!(s.corked().unwrap());
+        (&s).write_all(b"head").unwrap();
+        (&s).write_all(b"body").unwrap();
+    }
+    assert!(!s.corked().unwrap());
+
+    assert_eq!(b"headbody", &read_blocking(&accepted, 8)[..]);
+}
+
+#[test]
+fn accept_many() {
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = l.local_addr().unwrap();
+
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+    poll.register(&l, Token(0), Ready::readable(), PollOpt::edge()).unwrap();
+
+    let mut conns = Vec::new();
+    assert_eq!(io::ErrorKind::WouldBlock,
+               l.accept_many(&mut conns, 16).unwrap_err().kind());
+
+    let clients: Vec<_> = (0..5).map(|_| net::TcpStream::connect(&addr).unwrap()).collect();
+
+    while conns.len() < clients.len() {
+        match l.accept_many(&mut conns, 3) {
+            Ok(n) => assert!(n > 0 && n <= 3),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+            }
+            Err(e) => panic!("accept_many failed: {}", e),
+        }
+    }
+    assert_eq!(clients.len(), conns.len());
+
+    for &(ref s, ref a) in &conns {
+        assert_eq!(s.peer_addr().unwrap(), *a);
+        assert!(clients.iter().any(|c| c.local_addr().unwrap() == *a));
+
+        // Accepted sockets are nonblocking
+        let mut buf = [0; 1];
+        assert_eq!(io::ErrorKind::WouldBlock, (&*s).read(&mut buf).unwrap_err().kind());
+    }
+}
+
+#[test]
+fn pause_accepting() {
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = l.local_addr().unwrap();
+
+    l.set_fd_reserve(true).unwrap();
+    assert!(l.fd_reserve());
+
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+    poll.register(&l, Token(0), Ready::readable(), PollOpt::level()).unwrap();
+
+    l.pause_accepting(&poll).unwrap();
+    assert!(l.is_accepting_paused());
+
+    let _c1 = net::TcpStream::connect(&addr).unwrap();
+    poll.poll(&mut events, Some(Duration::from_millis(200))).unwrap();
+    assert!(events.is_empty());
+
+    // Reregistering while paused only changes what is restored
+    poll.reregister(&l, Token(1), Ready::readable(), PollOpt::level()).unwrap();
+    poll.poll(&mut events, Some(Duration::from_millis(200))).unwrap();
+    assert!(events.is_empty());
+
+    l.resume_accepting(&poll).unwrap();
+    assert!(!l.is_accepting_paused());
+    poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+    assert!(events.iter().any(|e| e.token() == Token(1) && e.readiness().is_readable()));
+
+    l.accept().unwrap();
+
+    l.set_fd_reserve(false).unwrap();
+    assert!(!l.fd_reserve());
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn reuseport_group_with_cpu_steering() {
+    let listeners = TcpListener::bind_reuseport(&"127.0.0.1:0".parse().unwrap(), 4).unwrap();
+    assert_eq!(4, listeners.len());
+
+    let addr = listeners[0].local_addr().unwrap();
+    assert!(listeners.iter().all(|l| l.local_addr().unwrap() == addr));
+
+    assert_eq!(io::ErrorKind::InvalidInput,
+               listeners[0].set_cpu_steering(0).unwrap_err().kind());
+    listeners[0].set_cpu_steering(listeners.len()).unwrap();
+
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+    for (i, l) in listeners.iter().enumerate() {
+        poll.register(l, Token(i), Ready::readable(), PollOpt::level()).unwrap();
+    }
+
+    let clients: Vec<_> = (0..8).map(|_| net::TcpStream::connect(&addr).unwrap()).collect();
+
+    let mut accepted = 0;
+    while accepted < clients.len() {
+        poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+        assert!(!events.is_empty());
+
+        for event in &events {
+            if let Ok(_) = listeners[event.token().0].accept() {
+                accepted += 1;
+            }
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn send_recv_with_flags() {
+    use mio::net::{RecvFlags, SendFlags};
+
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let l_poll = Poll::new().unwrap();
+    l_poll.register(&l, Token(0), Ready::readable(), PollOpt::level()).unwrap();
+
+    let s = TcpStream::connect(&l.local_addr().unwrap()).unwrap();
+    let (accepted, _) = accept_blocking(&l_poll, &l);
+
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+    poll.register(&accepted, Token(0), Ready::readable(), PollOpt::level()).unwrap();
+
+    let mut buf = [0; 16];
+    assert_eq!(io::ErrorKind::WouldBlock,
+               accepted.recv_with_flags(&mut buf, RecvFlags::empty()).unwrap_err().kind());
+
+    assert_eq!(4, s.send_with_flags(b"head", SendFlags::more() | SendFlags::no_signal()).unwrap());
+    let bufs: [&IoVec; 2] = [(&b"bo"[..]).into(), (&b"dytail"[..]).into()];
+    assert_eq!(8, s.send_bufs_with_flags(&bufs, SendFlags::no_signal()).unwrap());
+
+    while let Err(e) = accepted.recv_with_flags(&mut buf[..4], RecvFlags::peek() | RecvFlags::wait_all()) {
+        assert_eq!(io::ErrorKind::WouldBlock, e.kind());
+        poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+    }
+
+    // Wait for everything to arrive, then discard "head"
+    let mut n = 0;
+    while n < 12 {
+        poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+        n = accepted.recv_with_flags(&mut buf, RecvFlags::peek()).unwrap();
+    }
+    assert_eq!(4, accepted.recv_with_flags(&mut buf[..4], RecvFlags::trunc()).unwrap());
+
+    let mut a = [0; 4];
+    let mut b = [0; 4];
+    {
+        let mut bufs: [&mut IoVec; 2] = [(&mut a[..]).into(), (&mut b[..]).into()];
+        assert_eq!(8, accepted.recv_bufs_with_flags(&mut bufs, RecvFlags::empty()).unwrap());
+    }
+    assert_eq!(b"body", &a);
+    assert_eq!(b"tail", &b);
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn read_registered(poll: &Poll, s: &TcpStream, len: usize) -> Vec<u8> {
+    let mut events = Events::with_capacity(16);
+    let mut buf = vec![0; len];
+    let mut n = 0;
+
+    while n < len {
+        match (&*s).read(&mut buf[n..]) {
+            Ok(0) => break,
+            Ok(cnt) => n += cnt,
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+            }
+            Err(e) => panic!("read failed: {}", e),
+        }
+    }
+
+    buf.truncate(n);
+    buf
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn wait_for_priority(poll: &Poll) {
+    let mut events = Events::with_capacity(16);
+
+    loop {
+        poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+        assert!(!events.is_empty(), "timed out waiting for a priority event");
+
+        if events.iter().any(|e| UnixReady::from(e.readiness()).is_priority()) {
+            return;
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn send_recv_oob() {
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let l_poll = Poll::new().unwrap();
+    l_poll.register(&l, Token(0), Ready::readable(), PollOpt::level()).unwrap();
+
+    let s = TcpStream::connect(&l.local_addr().unwrap()).unwrap();
+    let (accepted, _) = accept_blocking(&l_poll, &l);
+
+    // Writable readiness is not mistaken for priority readiness
+    let s_poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+    s_poll.register(&s, Token(0), Ready::writable(), PollOpt::edge()).unwrap();
+    s_poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+    for event in &events {
+        assert!(event.readiness().is_writable());
+        assert!(!UnixReady::from(event.readiness()).is_priority());
+    }
+
+    let poll = Poll::new().unwrap();
+    poll.register(&accepted, Token(0), Ready::readable() | UnixReady::priority(),
+                  PollOpt::edge()).unwrap();
+
+    assert!(!accepted.oobinline().unwrap());
+
+    (&s).write_all(b"data").unwrap();
+    s.send_oob(b'!').unwrap();
+
+    wait_for_priority(&poll);
+    assert_eq!(b'!', accepted.recv_oob().unwrap());
+
+    // The urgent byte is not part of the regular data
+    assert_eq!(b"data", &read_registered(&poll, &accepted, 4)[..]);
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn oobinline() {
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let l_poll = Poll::new().unwrap();
+    l_poll.register(&l, Token(0), Ready::readable(), PollOpt::level()).unwrap();
+
+    let s = TcpStream::connect(&l.local_addr().unwrap()).unwrap();
+    let (accepted, _) = accept_blocking(&l_poll, &l);
+
+    accepted.set_oobinline(true).unwrap();
+    assert!(accepted.oobinline().unwrap());
+
+    let poll = Poll::new().unwrap();
+    poll.register(&accepted, Token(0), Ready::readable() | UnixReady::priority(),
+                  PollOpt::edge()).unwrap();
+
+    (&s).write_all(b"data").unwrap();
+    s.send_oob(b'!').unwrap();
+
+    wait_for_priority(&poll);
+    assert_eq!(b"data!", &read_registered(&poll, &accepted, 5)[..]);
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn ip_level_options() {
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let s = TcpStream::connect(&l.local_addr().unwrap()).unwrap();
+
+    s.set_tos(0x10).unwrap();
+    assert_eq!(0x10, s.tos().unwrap());
+    l.set_tos(0x28).unwrap();
+    assert_eq!(0x28, l.tos().unwrap());
+
+    assert!(!l.freebind().unwrap());
+    l.set_freebind(true).unwrap();
+    assert!(l.freebind().unwrap());
+
+    // These need privileges the tests may not have
+    match s.set_mark(7) {
+        Ok(()) => assert_eq!(7, s.mark().unwrap()),
+        Err(e) => assert_eq!(io::ErrorKind::PermissionDenied, e.kind()),
+    }
+    match s.bind_device(Some(b"lo")) {
+        Ok(()) => s.bind_device(None).unwrap(),
+        Err(e) => assert_eq!(io::ErrorKind::PermissionDenied, e.kind()),
+    }
+
+    if let Ok(l6) = TcpListener::bind(&"[::1]:0".parse().unwrap()) {
+        l6.set_tclass_v6(0x20).unwrap();
+        assert_eq!(0x20, l6.tclass_v6().unwrap());
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn transparent_proxy_options() {
+    let l = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let l_poll = Poll::new().unwrap();
+    l_poll.register(&l, Token(0), Ready::readable(), PollOpt::level()).unwrap();
+
+    assert!(!l.transparent().unwrap());
+    match l.set_transparent(true) {
+        Ok(()) => assert!(l.transparent().unwrap()),
+        Err(e) => assert_eq!(io::ErrorKind::PermissionDenied, e.kind()),
+    }
+
+    let _s = TcpStream::connect(&l.local_addr().unwrap()).unwrap();
+    let (accepted, _) = accept_blocking(&l_poll, &l);
+
+    // Without connection tracking there is no original destination, failing
+    // with `ENOENT`, or `ENOPROTOOPT` if conntrack is not loaded at all
+    match accepted.original_dst() {
+        Ok(dst) => assert_eq!(accepted.local_addr().unwrap(), dst),
+        Err(e) => {
+            let errno = e.raw_os_error();
+            assert!(errno == Some(2) || errno == Some(92), "unexpected error: {:?}", e);
+        }
+    }
+}
@@ -0,0 +1,196 @@
+use mio::{Events, Poll, PollOpt, Ready, Token};
+use mio::timer;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+pub fn test_timer_len_and_is_empty() {
+    let mut timer = timer::Builder::default()
+        .tick_duration(Duration::from_millis(10))
+        .build();
+
+    assert!(timer.is_empty());
+    assert_eq!(0, timer.len());
+
+    let a = timer.set_timeout(Duration::from_millis(200), "a").unwrap();
+    timer.set_timeout(Duration::from_millis(400), "b").unwrap();
+
+    assert!(!timer.is_empty());
+    assert_eq!(2, timer.len());
+
+    assert_eq!(Some("a"), timer.cancel_timeout(&a));
+    assert_eq!(1, timer.len());
+}
+
+#[test]
+pub fn test_timer_next_deadline() {
+    let mut timer = timer::Builder::default()
+        .tick_duration(Duration::from_millis(10))
+        .build();
+
+    assert!(timer.next_deadline().is_none());
+
+    let before = Instant::now();
+    timer.set_timeout(Duration::from_millis(500), "late").unwrap();
+    timer.set_timeout(Duration::from_millis(200), "early").unwrap();
+
+    let deadline = timer.next_deadline().unwrap();
+
+    // Allow for rounding to the tick granularity
+    assert!(deadline >= before + Duration::from_millis(180), "deadline too early");
+    assert!(deadline <= Instant::now() + Duration::from_millis(220), "deadline too late");
+}
+
+#[test]
+pub fn test_timer_drain() {
+    let mut timer = timer::Builder::default()
+        .tick_duration(Duration::from_millis(10))
+        .num_slots(8)
+        .build();
+
+    for i in 0..20 {
+        timer.set_timeout(Duration::from_millis(10 * i), i).unwrap();
+    }
+
+    let mut drained: Vec<u64> = timer.drain().collect();
+    drained.sort();
+
+    assert_eq!((0..20).collect::<Vec<_>>(), drained);
+    assert!(timer.is_empty());
+    assert!(timer.next_deadline().is_none());
+    assert!(timer.poll().is_none());
+
+    // The timer is still usable after draining
+    timer.set_timeout(Duration::from_millis(0), 100).unwrap();
+    assert_eq!(1, timer.len());
+
+    // Dropping the iterator early still cancels everything
+    drop(timer.drain());
+    assert!(timer.is_empty());
+}
+
+#[test]
+pub fn test_timer_grows_past_initial_capacity() {
+    let mut timer = timer::Builder::default()
+        .tick_duration(Duration::from_millis(10))
+        .capacity(4)
+        .build();
+
+    let timeouts: Vec<_> = (0..100)
+        .map(|i| timer.set_timeout(Duration::from_millis(1_000), i).unwrap())
+        .collect();
+
+    assert_eq!(100, timer.len());
+
+    // Handles created before the timer grew are still valid
+    for (i, timeout) in timeouts.iter().enumerate() {
+        assert_eq!(Some(i), timer.cancel_timeout(timeout));
+    }
+
+    assert!(timer.is_empty());
+}
+
+#[test]
+pub fn test_timer_max_capacity() {
+    let mut timer = timer::Builder::default()
+        .tick_duration(Duration::from_millis(10))
+        .capacity(1)
+        .max_capacity(2)
+        .build();
+
+    let a = timer.set_timeout(Duration::from_millis(1_000), "a").unwrap();
+    timer.set_timeout(Duration::from_millis(1_000), "b").unwrap();
+
+    assert!(timer.set_timeout(Duration::from_millis(1_000), "c").is_err());
+    assert_eq!(2, timer.len());
+
+    // Canceling a timeout frees up room for another
+    timer.cancel_timeout(&a).unwrap();
+    timer.set_timeout(Duration::from_millis(1_000), "c").unwrap();
+}
+
+#[test]
+pub fn test_timer_handle_is_send_and_sync() {
+    fn is_send_sync<T: Send + Sync>() {}
+    is_send_sync::<timer::Handle<String>>();
+}
+
+#[test]
+pub fn test_timer_handle_set_timeout_from_thread() {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+    let mut timer = timer::Builder::default()
+        .tick_duration(Duration::from_millis(10))
+        .build();
+
+    poll.register(&timer, Token(0), Ready::readable(), PollOpt::edge()).unwrap();
+
+    // A far away local timeout should not delay the remote one
+    timer.set_timeout(Duration::from_secs(60), "local").unwrap();
+
+    let handle = timer.handle();
+    thread::spawn(move || {
+        handle.set_timeout(Duration::from_millis(50), "remote");
+    }).join().unwrap();
+
+    poll.poll(&mut events, Some(Duration::from_secs(5))).unwrap();
+
+    let event = events.iter().next().expect("no timer event");
+    assert_eq!(Token(0), event.token());
+    assert!(event.readiness().is_readable());
+
+    assert_eq!(Some("remote"), timer.poll());
+    assert_eq!(1, timer.len());
+}
+
+#[test]
+pub fn test_timer_handle_cancel_timeout() {
+    let mut timer = timer::Builder::default()
+        .tick_duration(Duration::from_millis(10))
+        .build();
+    let handle = timer.handle();
+
+    // Canceled before the timer has seen it
+    let a = handle.set_timeout(Duration::from_millis(0), "a");
+    handle.cancel_timeout(&a);
+
+    // Canceled after the timer has seen it
+    let b = handle.set_timeout(Duration::from_millis(1_000), "b");
+    assert_eq!(None, timer.poll());
+    assert_eq!(1, timer.len());
+
+    handle.cancel_timeout(&b);
+    assert_eq!(None, timer.poll());
+    assert!(timer.is_empty());
+}
+
+#[test]
+pub fn test_timer_handle_zero_delay_then_later() {
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+    let mut timer = timer::Builder::default()
+        .tick_duration(Duration::from_millis(10))
+        .build();
+
+    poll.register(&timer, Token(0), Ready::readable(), PollOpt::edge()).unwrap();
+
+    // A zero delay right after start used to schedule a wakeup for tick 0,
+    // which shut down the wakeup thread
+    let handle = timer.handle();
+    handle.set_timeout(Duration::from_millis(0), "now");
+    handle.set_timeout(Duration::from_millis(100), "later");
+
+    let deadline = Instant::now() + Duration::from_millis(500);
+    let mut fired = vec![];
+
+    while fired.len() < 2 {
+        poll.poll(&mut events, Some(Duration::from_secs(1))).unwrap();
+        assert!(Instant::now() < deadline, "timer did not fire; fired={:?}", fired);
+
+        while let Some(state) = timer.poll() {
+            fired.push(state);
+        }
+    }
+
+    assert_eq!(vec!["now", "later"], fired);
+}
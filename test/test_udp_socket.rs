@@ -1,5 +1,5 @@
 use mio::{Events, Poll, PollOpt, Ready, Token};
-use mio::net::UdpSocket;
+use mio::net::{UdpSocket, UdpSocketBuilder};
 use bytes::{Buf, RingBuf, SliceBuf, MutBuf};
 use std::io::ErrorKind;
 use std::str;
@@ -173,3 +173,87 @@ pub fn test_udp_socket_discard() {
         }
     }
 }
+
+#[test]
+pub fn test_udp_socket_builder() {
+    let builder = UdpSocketBuilder::new_v4().unwrap();
+    builder.set_reuseaddr(true).unwrap();
+    assert!(builder.reuseaddr().unwrap());
+
+    builder.set_recv_buffer_size(64 * 1024).unwrap();
+    assert!(builder.recv_buffer_size().unwrap() >= 64 * 1024);
+
+    let rx = builder.bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let tx = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+
+    test_send_recv_udp(tx, rx, false);
+}
+
+#[cfg(unix)]
+#[test]
+pub fn test_udp_socket_builder_reuseport() {
+    let first = UdpSocketBuilder::new_v4().unwrap();
+    first.set_reuseport(true).unwrap();
+    assert!(first.reuseport().unwrap());
+
+    let first = first.bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = first.local_addr().unwrap();
+
+    let second = UdpSocketBuilder::new_v4().unwrap();
+    second.set_reuseport(true).unwrap();
+    let second = second.bind(&addr).unwrap();
+
+    assert_eq!(addr, second.local_addr().unwrap());
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+pub fn test_udp_ip_level_options() {
+    let socket = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+
+    socket.set_tos(0x10).unwrap();
+    assert_eq!(0x10, socket.tos().unwrap());
+
+    socket.set_freebind(true).unwrap();
+    assert!(socket.freebind().unwrap());
+
+    match socket.set_mark(7) {
+        Ok(()) => assert_eq!(7, socket.mark().unwrap()),
+        Err(e) => assert_eq!(ErrorKind::PermissionDenied, e.kind()),
+    }
+    match socket.bind_device(Some(b"lo")) {
+        Ok(()) => socket.bind_device(None).unwrap(),
+        Err(e) => assert_eq!(ErrorKind::PermissionDenied, e.kind()),
+    }
+
+    if let Ok(socket6) = UdpSocket::bind(&"[::1]:0".parse().unwrap()) {
+        socket6.set_tclass_v6(0x20).unwrap();
+        assert_eq!(0x20, socket6.tclass_v6().unwrap());
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+pub fn test_udp_recv_from_with_dst() {
+    let tx = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let rx = UdpSocket::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+
+    assert!(!rx.recv_orig_dst_addr().unwrap());
+    rx.set_recv_orig_dst_addr(true).unwrap();
+    assert!(rx.recv_orig_dst_addr().unwrap());
+
+    let poll = Poll::new().unwrap();
+    let mut events = Events::with_capacity(16);
+    poll.register(&rx, LISTENER, Ready::readable(), PollOpt::edge()).unwrap();
+
+    tx.send_to(b"hello", &rx.local_addr().unwrap()).unwrap();
+    poll.poll(&mut events, Some(time::Duration::from_secs(5))).unwrap();
+
+    let mut buf = [0; 16];
+    let (n, src, dst) = rx.recv_from_with_dst(&mut buf).unwrap();
+    assert_eq!(b"hello", &buf[..n]);
+    assert_eq!(tx.local_addr().unwrap(), src);
+    assert_eq!(Some(rx.local_addr().unwrap()), dst);
+
+    assert_eq!(ErrorKind::WouldBlock, rx.recv_from_with_dst(&mut buf).unwrap_err().kind());
+}